@@ -1,8 +1,28 @@
 #![allow(dead_code)]
+#[cfg(feature = "rand")]
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
+use std::cell::RefCell;
+use std::cmp;
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Add, AddAssign, Mul, Sub, SubAssign};
+use std::rc::Rc;
+use std::str::FromStr;
 
 /// Character stat.
-#[derive(PartialEq, Eq, Hash, Clone)]
+///
+/// Stats are ordered canonically by declaration, matching `Stat::all`:
+/// Str < Int < Swi < Dex < Vit < Luck < Cha.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Stat {
     /// Strength.
     Str,
@@ -10,151 +30,4780 @@ pub enum Stat {
     Int,
     /// Swiftness.
     Swi,
+    /// Dexterity.
+    Dex,
+    /// Vitality.
+    Vit,
+    /// Luck.
+    Luck,
+    /// Charisma.
+    Cha,
+}
+
+/// Derived stat, computed from primary stats.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DerivedStat {
+    /// Health points.
+    Hp,
+    /// Mana points.
+    Mp,
+    /// Attack.
+    Atk,
+    /// Defense.
+    Def,
+}
+
+/// Stat error.
+#[derive(PartialEq, Debug)]
+pub enum StatError {
+    /// No base value is defined for the stat.
+    MissingBase(Stat),
+    /// The string does not name a stat.
+    UnknownStat(String),
+    /// Not enough unspent stat points are available.
+    OutOfPoints,
+    /// The binary data is truncated or malformed.
+    MalformedData,
+    /// A debuff was constructed with a positive value.
+    PositiveDebuff,
+    /// A multiplier value is NaN or infinite.
+    NonFinite,
+    /// No multiplier from the source is active.
+    UnknownSource(String),
+    /// A stat would end up depending on itself.
+    CyclicDependency,
+    /// A percentage is below -100%.
+    PercentOutOfRange,
+    /// The string is not valid dice notation.
+    InvalidNotation(String),
+}
+
+/// Rounding mode.
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RoundingMode {
+    /// Round half away from zero.
+    #[default]
+    Round,
+    /// Round towards negative infinity.
+    Floor,
+    /// Round towards positive infinity.
+    Ceil,
+    /// Round towards zero.
+    Truncate,
+}
+
+/// Multiplier stacking mode.
+#[derive(PartialEq, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StackingMode {
+    /// Multipliers are summed, so two +10% multipliers yield +20%.
+    #[default]
+    Additive,
+    /// Multipliers are compounded, so two +10% multipliers yield +21%.
+    Multiplicative,
+}
+
+/// Multiplier curve, weighting repeated stacks of the same multiplier.
+#[derive(PartialEq, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MultiplierCurve {
+    /// Every stack contributes its full value.
+    #[default]
+    Linear,
+    /// The nth stack contributes 1/n of its value.
+    DiminishingHarmonic,
+    /// The nth stack contributes 1/(1 + ln n) of its value.
+    Logarithmic,
+}
+
+/// Cost curve, pricing stat points allocated to the same stat.
+#[derive(PartialEq, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CostCurve {
+    /// Every point costs 1.
+    #[default]
+    Flat,
+    /// The nth point costs n.
+    Linear,
+    /// The nth point costs n².
+    Quadratic,
 }
 
 /// Character stats.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CharacterStats {
+    base: BaseStore,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    flats: HashMap<Stat, f32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    multipliers: HashMap<Stat, Vec<Multiplier>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    rounding: RoundingMode,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    clamps: HashMap<Stat, (Option<f32>, Option<f32>)>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    floors: HashMap<Stat, i32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    stacking: StackingMode,
+    #[cfg_attr(feature = "serde", serde(default))]
+    curve: MultiplierCurve,
+    #[cfg_attr(feature = "serde", serde(default = "first_level"))]
+    level: u32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    growth: HashMap<Stat, f32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    points: u32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    allocated: HashMap<Stat, u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    spent: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    cost_curve: CostCurve,
+    #[cfg_attr(feature = "serde", serde(default))]
+    debuffs: Vec<Debuff>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    layers: Vec<(LayerId, StatLayer)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    next_layer: u32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    transition_ticks: HashMap<Stat, u32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    transitions: HashMap<Stat, Transition>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    conversions: Vec<Conversion>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    scalings: Vec<Scaling>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    immunities: HashMap<Stat, bool>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    globals: Vec<f32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    overrides: HashMap<Stat, i32>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    soft_caps: HashMap<Stat, (f32, f32)>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    regen: HashMap<Stat, (f32, f32)>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, serialize_with = "serialize_ordered")
+    )]
+    multiplier_caps: HashMap<Stat, f32>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    conditionals: Vec<ConditionalMultiplier>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    derived: HashMap<DerivedStat, Formula>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hooks: Hooks,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history: History,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cache: Option<RefCell<HashMap<Stat, i32>>>,
+}
+
+/// Storage of the base values of `CharacterStats`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "HashMap<Stat, f32>", into = "BTreeMap<Stat, f32>")
+)]
+enum BaseStore {
+    /// Sparse map.
+    Map(HashMap<Stat, f32>),
+    /// Array indexed by `Stat::index`, so lookups skip hashing.
+    Array([Option<f32>; Stat::COUNT]),
+}
+
+/// Formula of a derived stat.
+#[derive(Clone)]
+struct Formula(Rc<dyn Fn(&CharacterStats) -> f32>);
+
+/// Condition of a `ConditionalMultiplier`.
+#[derive(Clone)]
+struct Condition(Rc<dyn Fn(&CharacterStats) -> bool>);
+
+/// Callbacks registered on `CharacterStats`.
+#[derive(Default)]
+struct Hooks {
+    observers: Vec<Box<dyn FnMut(Stat, i32)>>,
+    thresholds: Vec<Threshold>,
+    expiry: Vec<ExpiryCallback>,
+}
+
+/// Callback receiving a multiplier removed by `CharacterStats::tick`.
+type ExpiryCallback = Box<dyn FnMut(&Multiplier)>;
+
+/// Multiplier contribution as `(source, weighted value, priority)`.
+type MultiplierEntry<'a> = (Option<&'a str>, f32, i32);
+
+/// Callback fired when the effective value of a stat crosses a threshold.
+struct Threshold {
+    stat: Stat,
+    value: i32,
+    reached: bool,
+    on_cross: Box<dyn FnMut(bool)>,
+}
+
+/// Operation recorded for `CharacterStats::undo` and `CharacterStats::redo`.
+#[derive(Clone, Debug)]
+enum Operation {
+    AddMultiplier(Multiplier),
+    /// Removed multiplier and its former position.
+    SubMultiplier(Multiplier, usize),
+    /// Stat, previous base value and new base value.
+    SetBase(Stat, Option<f32>, f32),
+}
+
+/// Undo and redo stacks of `CharacterStats`.
+#[derive(Clone, Debug)]
+struct History {
+    undone: Vec<Operation>,
+    done: Vec<Operation>,
+    depth: usize,
+}
+
+/// Breakdown of how a stat is computed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatBreakdown {
+    /// Base value.
+    pub base: f32,
+    /// Summed flat bonus.
+    pub flat: f32,
+    /// Contribution and source of every active multiplier.
+    pub multipliers: Vec<(Option<String>, f32)>,
+    /// Value before rounding, or the override if one is set.
+    pub pre_round: f32,
+    /// Final, rounded value.
+    pub final_value: i32,
+}
+
+/// Description of an active multiplier, such as an entry of a buff bar.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EffectInfo {
+    /// Affected stat.
+    pub stat: Stat,
+    /// Value of the multiplier.
+    pub value: f32,
+    /// Ticks until the multiplier expires, or `None` if it is permanent.
+    pub remaining: Option<u32>,
+    /// Source of the multiplier.
+    pub source: Option<String>,
+}
+
+/// Snapshot of the full state of `CharacterStats`.
+#[derive(Clone, Debug)]
+pub struct StatSnapshot {
+    stats: CharacterStats,
+}
+
+/// Read-only view of the base and effective stats of `CharacterStats`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrozenStats {
+    base: HashMap<Stat, f32>,
+    effective: HashMap<Stat, i32>,
+}
+
+/// Bundle of the multipliers of `CharacterStats`, such as for buff templates.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MultiplierSet {
+    multipliers: Vec<Multiplier>,
+}
+
+/// Builder for `CharacterStats`.
+#[derive(Default)]
+pub struct CharacterStatsBuilder {
     base: HashMap<Stat, f32>,
+    multipliers: Vec<Multiplier>,
+}
+
+/// Layer of flat bonuses and multipliers, such as a piece of equipment.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StatLayer {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_ordered"))]
+    flats: HashMap<Stat, f32>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_ordered"))]
     multipliers: HashMap<Stat, f32>,
 }
 
+/// Bundle of flat and percentage bonuses applied and removed as a unit,
+/// such as an item granting "+5 Str and +10% Str".
+#[derive(Clone, Debug, Default)]
+pub struct Effect {
+    layer: StatLayer,
+}
+
+/// Handle of an `Effect` applied to `CharacterStats`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct EffectHandle(LayerId);
+
+/// Handle of a multiplier applied to several stats by `CharacterStats::add_masked_multiplier`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct MaskHandle(LayerId);
+
+/// Bit patterns of everything that determines the effective value of one stat,
+/// compared and hashed by `CharacterStats`.
+#[derive(PartialEq, Eq, Hash)]
+struct Fingerprint {
+    base: Option<u32>,
+    flat: u32,
+    multiplier: u32,
+    conversions: Vec<(Stat, u32)>,
+    multiplier_cap: Option<u32>,
+    soft_cap: Option<(u32, u32)>,
+    clamp: Option<(Option<u32>, Option<u32>)>,
+    floor: Option<i32>,
+    override_value: Option<i32>,
+}
+
+/// Bonus granting a share of the effective value of one stat to another.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Conversion {
+    from: Stat,
+    to: Stat,
+    ratio: f32,
+}
+
+/// Multiplier on one stat growing with the effective value of another.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Scaling {
+    source: Stat,
+    target: Stat,
+    per_point: f32,
+}
+
+/// Interpolation of a stat from its previous effective value toward its current one.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Transition {
+    from: f32,
+    elapsed: u32,
+    ticks: u32,
+}
+
+/// Identifier of a `StatLayer` pushed onto `CharacterStats`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LayerId(u32);
+
 /// Stat multiplier.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Multiplier {
     stat: Stat,
     value: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    source: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    duration: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    category: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_stacks: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    priority: i32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    decay: Option<f32>,
+}
+
+/// Multiplier value checked to lie in a sane range.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Percent(f32);
+
+/// Multiplier that only applies while a condition holds.
+#[derive(Clone, Debug)]
+pub struct ConditionalMultiplier {
+    mult: Multiplier,
+    condition: Condition,
+}
+
+/// Named debuff, reducing a stat by a percentage.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Debuff {
+    name: String,
+    mult: Multiplier,
+}
+
+/// Flat stat bonus.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FlatBonus {
+    stat: Stat,
+    value: f32,
 }
 
 impl CharacterStats {
     /// Constructs new `CharacterStats`.
     pub fn new(base: HashMap<Stat, f32>) -> Self {
+        CharacterStats::with_base(BaseStore::Map(base))
+    }
+    /// Constructs new `CharacterStats` storing base values in a fixed array
+    /// indexed by `Stat::index`, so looking up a base value involves no hashing.
+    /// Every stat is defined.
+    ///
+    /// # Internals
+    /// Modifiers are kept in hash maps, but a map is only hashed into once it holds an entry,
+    /// so reading a stat of a character without modifiers is an array index plus multiply.
+    /// Otherwise behaves exactly like `CharacterStats` constructed with `new`.
+    pub fn new_array(base: [f32; Stat::COUNT]) -> Self {
+        let mut slots = [None; Stat::COUNT];
+        for (slot, value) in slots.iter_mut().zip(base.iter()) {
+            *slot = Some(*value);
+        }
+        CharacterStats::with_base(BaseStore::Array(slots))
+    }
+    /// Constructs new `CharacterStats` on top of existing base storage.
+    fn with_base(base: BaseStore) -> Self {
         CharacterStats {
-            base: base,
+            base,
+            flats: HashMap::new(),
             multipliers: HashMap::new(),
+            rounding: RoundingMode::default(),
+            clamps: HashMap::new(),
+            floors: HashMap::new(),
+            stacking: StackingMode::default(),
+            curve: MultiplierCurve::default(),
+            level: first_level(),
+            growth: HashMap::new(),
+            points: 0,
+            allocated: HashMap::new(),
+            spent: 0,
+            cost_curve: CostCurve::default(),
+            debuffs: Vec::new(),
+            layers: Vec::new(),
+            next_layer: 0,
+            transition_ticks: HashMap::new(),
+            transitions: HashMap::new(),
+            conversions: Vec::new(),
+            scalings: Vec::new(),
+            immunities: HashMap::new(),
+            globals: Vec::new(),
+            overrides: HashMap::new(),
+            soft_caps: HashMap::new(),
+            regen: HashMap::new(),
+            multiplier_caps: HashMap::new(),
+            conditionals: Vec::new(),
+            derived: HashMap::new(),
+            hooks: Hooks::default(),
+            history: History::default(),
+            cache: None,
+        }
+    }
+    /// Constructs new `CharacterStats` with `total_points` randomly distributed
+    /// across the given stats, so the base values sum to `total_points`.
+    ///
+    /// # Internals
+    /// Every point is assigned to one of the stats with equal probability.
+    /// Returns `CharacterStats` without any base values if `stats` is empty.
+    #[cfg(feature = "rand")]
+    pub fn roll_stats(total_points: u32, stats: &[Stat], rng: &mut impl Rng) -> Self {
+        let mut base: HashMap<Stat, f32> = stats.iter().map(|stat| (stat.clone(), 0f32)).collect();
+        if !stats.is_empty() {
+            for _ in 0..total_points {
+                let stat = &stats[rng.gen_range(0..stats.len())];
+                *base.entry(stat.clone()).or_insert(0f32) += 1f32;
+            }
+        }
+        CharacterStats::new(base)
+    }
+    /// Constructs new `CharacterStats` rolling the base value of every given stat
+    /// independently in `NdM` dice notation, such as "3d6" for the sum of three six-sided dice.
+    ///
+    /// Returns `StatError::InvalidNotation` if the notation is not of the form `NdM`
+    /// with 1 to 100 dice of 1 to 1000 sides each.
+    #[cfg(feature = "rand")]
+    pub fn roll_from_notation(
+        notation: &str,
+        stats: &[Stat],
+        rng: &mut impl Rng,
+    ) -> Result<CharacterStats, StatError> {
+        let invalid = || StatError::InvalidNotation(notation.to_string());
+        let (count, sides) = match notation.trim().split_once('d') {
+            Some((count, sides)) => (count.parse::<u32>(), sides.parse::<usize>()),
+            None => return Err(invalid()),
+        };
+        let (count, sides) = match (count, sides) {
+            (Ok(count), Ok(sides))
+                if (1..=MAX_DICE).contains(&count) && (1..=MAX_SIDES).contains(&sides) =>
+            {
+                (count, sides)
+            }
+            _ => return Err(invalid()),
+        };
+        let base = stats
+            .iter()
+            .map(|stat| {
+                let total = (0..count).fold(0_usize, |total, _| {
+                    total.saturating_add(rng.gen_range(0..sides) + 1)
+                });
+                (stat.clone(), total as f32)
+            })
+            .collect();
+        Ok(CharacterStats::new(base))
+    }
+    /// Constructs new `CharacterStats` from `(Stat, base)` pairs.
+    /// Later pairs replace earlier ones for the same stat.
+    pub fn from_base_slice(bases: &[(Stat, f32)]) -> CharacterStats {
+        bases.iter().cloned().collect()
+    }
+    /// Constructs new `CharacterStats` with every `Stat` set to the same base value,
+    /// such as for test characters and default enemies.
+    pub fn uniform(value: f32) -> CharacterStats {
+        CharacterStats::new_array([value; Stat::COUNT])
+    }
+    /// Gets a specific `Stat`.
+    /// Flat bonuses and multipliers are applied in the process.
+    ///
+    /// # Panics
+    /// Panics if no base value is defined for the stat.
+    /// Use `try_get_stat` to handle missing stats gracefully.
+    pub fn get_stat(&self, stat: Stat) -> i32 {
+        match self.try_get_stat(stat) {
+            Ok(value) => value,
+            Err(_) => panic!("no base value defined for stat"),
+        }
+    }
+    /// Gets a specific `Stat`.
+    /// Flat bonuses and multipliers are applied in the process,
+    /// unless an override is set for the stat.
+    /// Values outside the range of `i32` saturate to its bounds,
+    /// and values below the floor of the stat are raised to it.
+    ///
+    /// Returns `StatError::MissingBase` if no base value is defined for the stat.
+    pub fn try_get_stat(&self, stat: Stat) -> Result<i32, StatError> {
+        if lookup(&self.transitions, &stat).is_some() && lookup(&self.overrides, &stat).is_none() {
+            return match self.displayed_f32(&stat) {
+                Some(value) => Ok(self.finish(&stat, value)),
+                None => Err(StatError::MissingBase(stat)),
+            };
+        }
+        self.target_stat(stat)
+    }
+    /// Gets a specific `Stat` like `try_get_stat`, but guards against corrupt values
+    /// that would otherwise round to 0.
+    ///
+    /// Returns `None` if no base value is defined for the stat,
+    /// or if its value before rounding is NaN or infinite.
+    pub fn checked_get_stat(&self, stat: Stat) -> Option<i32> {
+        if !self.get_stat_f32(stat.clone())?.is_finite() {
+            return None;
+        }
+        self.try_get_stat(stat).ok()
+    }
+    /// Gets a specific `Stat`, ignoring active transitions.
+    fn target_stat(&self, stat: Stat) -> Result<i32, StatError> {
+        if let Some(value) = lookup(&self.overrides, &stat) {
+            return Ok(*value);
+        }
+        let cache = match self.cache {
+            Some(ref cache) => cache,
+            None => return self.compute_stat(stat),
+        };
+        if let Some(value) = cache.borrow().get(&stat) {
+            return Ok(*value);
+        }
+        let value = self.compute_stat(stat.clone())?;
+        cache.borrow_mut().insert(stat, value);
+        Ok(value)
+    }
+    /// Caches effective stats, so repeated reads skip recomputation.
+    ///
+    /// # Internals
+    /// Values are computed lazily on first read and the cache is invalidated on every mutation.
+    /// Conditional multipliers are assumed to depend only on the stats themselves.
+    pub fn enable_cache(&mut self) {
+        if self.cache.is_none() {
+            self.cache = Some(RefCell::new(HashMap::new()));
+        }
+    }
+    /// Computes a specific `Stat`, bypassing the cache.
+    fn compute_stat(&self, stat: Stat) -> Result<i32, StatError> {
+        match self.get_stat_f32(stat.clone()) {
+            Some(value) => Ok(self.finish(&stat, value)),
+            None => Err(StatError::MissingBase(stat)),
+        }
+    }
+    /// Rounds an unrounded stat value and raises it to the floor of the stat.
+    fn finish(&self, stat: &Stat, value: f32) -> i32 {
+        let value = saturate(self.rounding.apply(value));
+        match lookup(&self.floors, stat) {
+            Some(floor) => value.max(*floor),
+            None => value,
+        }
+    }
+    /// Gets a specific `Stat` without rounding, interpolated if a transition is active.
+    fn displayed_f32(&self, stat: &Stat) -> Option<f32> {
+        let target = self.get_stat_f32(stat.clone())?;
+        match self.transitions.get(stat) {
+            Some(transition) => {
+                let progress = transition.elapsed as f32 / transition.ticks as f32;
+                Some(transition.from + (target - transition.from) * progress)
+            }
+            None => Some(target),
+        }
+    }
+    /// Makes changes to the effective value of a specific stat ramp in over a number of ticks.
+    ///
+    /// # Internals
+    /// After a mutation changes the stat, `get_stat` interpolates linearly from the
+    /// previously returned value toward the new one as `tick` is called, and reaches
+    /// it after `ticks` ticks. Observers and thresholds see the new value immediately.
+    /// A duration of 0 disables the transition.
+    pub fn set_transition(&mut self, stat: Stat, ticks: u32) {
+        if ticks == 0 {
+            self.transition_ticks.remove(&stat);
+            self.transitions.remove(&stat);
+        } else {
+            self.transition_ticks.insert(stat, ticks);
+        }
+    }
+    /// Gets a specific `Stat` without rounding.
+    /// Flat bonuses, multipliers and clamps are applied in the process,
+    /// unless an override is set for the stat.
+    ///
+    /// Returns `None` if neither a base value nor an override is defined for the stat.
+    pub fn get_stat_f32(&self, stat: Stat) -> Option<f32> {
+        if let Some(value) = lookup(&self.overrides, &stat) {
+            return Some(*value as f32);
+        }
+        let base = match self.base.get(&stat) {
+            Some(val) => *val,
+            None => return None,
+        };
+        let flat = self.get_flat(&stat);
+        let mut multiplier = self.get_multiplier(stat.clone());
+        if let Some(&cap) = lookup(&self.multiplier_caps, &stat) {
+            multiplier = multiplier.clamp(-cap.abs(), cap.abs());
+        }
+        let multiplier = 1_f32 + multiplier;
+        let mut value = (base + flat) * multiplier;
+        for conv in self.conversions.iter().filter(|conv| conv.to == stat) {
+            value += conv.ratio * self.get_stat_f32(conv.from.clone()).unwrap_or(0f32);
+        }
+        if let Some(&(cap, reduction)) = lookup(&self.soft_caps, &stat) {
+            if value > cap {
+                value = cap + (value - cap) * (1_f32 - reduction);
+            }
+        }
+        if let Some(&(min, max)) = lookup(&self.clamps, &stat) {
+            if let Some(min) = min {
+                value = value.max(min);
+            }
+            if let Some(max) = max {
+                value = value.min(max);
+            }
+        }
+        Some(value)
+    }
+    /// Gets a specific `Stat` in fractions of `1 / scale`, so a scale of 100 yields centi-stats.
+    /// The scaled value is rounded according to the `RoundingMode`
+    /// and the floor of the stat is scaled along with it, as is an override.
+    ///
+    /// Returns `None` if no base value is defined for the stat.
+    pub fn get_stat_scaled(&self, stat: Stat, scale: u32) -> Option<i64> {
+        if let Some(value) = self.overrides.get(&stat) {
+            return Some(*value as i64 * scale as i64);
+        }
+        let value = self.get_stat_f32(stat.clone())? * scale as f32;
+        let value = self.rounding.apply(value) as i64;
+        match self.floors.get(&stat) {
+            Some(floor) => Some(value.max(*floor as i64 * scale as i64)),
+            None => Some(value),
+        }
+    }
+    /// Checks that a base value is defined for every required stat.
+    ///
+    /// Returns `StatError::MissingBase` for the first required stat without a base value.
+    pub fn validate(&self, required: &[Stat]) -> Result<(), StatError> {
+        match required.iter().find(|stat| !self.base.contains_key(stat)) {
+            Some(stat) => Err(StatError::MissingBase(stat.clone())),
+            None => Ok(()),
+        }
+    }
+    /// Checks whether every listed stat is defined and its effective value reaches the minimum,
+    /// such as the requirements of an item.
+    pub fn meets_requirements(&self, reqs: &[(Stat, i32)]) -> bool {
+        reqs.iter().all(|(stat, min)| {
+            self.try_get_stat(stat.clone())
+                .is_ok_and(|value| value >= *min)
+        })
+    }
+    /// Gets every `Stat` with a base value, in `Stat::all` order.
+    pub fn defined_stats(&self) -> Vec<Stat> {
+        Stat::all()
+            .iter()
+            .filter(|stat| self.base.contains_key(stat))
+            .cloned()
+            .collect()
+    }
+    /// Gets every defined `Stat`.
+    /// Flat bonuses and multipliers are applied in the process.
+    pub fn effective_stats(&self) -> HashMap<Stat, i32> {
+        self.base
+            .keys()
+            .map(|stat| (stat.clone(), self.get_stat(stat.clone())))
+            .collect()
+    }
+    /// Returns a clone with an additional multiplier, leaving `self` untouched.
+    /// Change callbacks are not carried over to the clone.
+    pub fn with_multiplier(&self, mult: &Multiplier) -> CharacterStats {
+        let mut stats = self.clone();
+        stats.add_multiplier(mult);
+        stats
+    }
+    /// Computes the difference of each effective stat to a baseline.
+    /// Stats that are not defined on both sides are skipped.
+    pub fn delta(&self, baseline: &CharacterStats) -> HashMap<Stat, i32> {
+        self.base
+            .keys()
+            .filter(|stat| baseline.base.contains_key(stat))
+            .map(|stat| {
+                let diff = self
+                    .get_stat(stat.clone())
+                    .saturating_sub(baseline.get_stat(stat.clone()));
+                (stat.clone(), diff)
+            })
+            .collect()
+    }
+    /// Lists every effective stat that changes from `self` to `other`, in `Stat` order,
+    /// together with the signed change. Stats that are not defined on both sides are skipped.
+    pub fn diff(&self, other: &CharacterStats) -> Vec<(Stat, i32)> {
+        let mut changes: Vec<(Stat, i32)> = other
+            .delta(self)
+            .into_iter()
+            .filter(|&(_, change)| change != 0)
+            .collect();
+        changes.sort();
+        changes
+    }
+    /// Computes the percentage by which an effective stat would change if a multiplier
+    /// were added, without mutating `self`.
+    ///
+    /// Returns `StatError::MissingBase` if no base value is defined for the stat of the multiplier.
+    pub fn preview_change(&self, mult: &Multiplier) -> Result<f32, StatError> {
+        let before = self.try_get_stat(mult.stat.clone())?;
+        let after = self.with_multiplier(mult).try_get_stat(mult.stat.clone())?;
+        Ok(CharacterStats::percent_change(before, after))
+    }
+    /// Computes the percentage change between two stat values, so 10 to 11 yields 10.0.
+    /// Returns 0.0 if `before` is zero.
+    pub fn percent_change(before: i32, after: i32) -> f32 {
+        if before == 0 {
+            return 0_f32;
+        }
+        (after as f32 - before as f32) / before as f32 * 100_f32
+    }
+    /// Sums every defined `Stat`, saturating at the bounds of `i32`.
+    /// Flat bonuses and multipliers are applied in the process.
+    pub fn total(&self) -> i32 {
+        self.effective_stats()
+            .values()
+            .fold(0, |acc, value| acc.saturating_add(*value))
+    }
+    /// Sums every defined `Stat`, scaled by its weight.
+    /// Stats without a weight count once.
+    pub fn total_weighted(&self, weights: &HashMap<Stat, f32>) -> f32 {
+        self.effective_stats()
+            .iter()
+            .map(|(stat, value)| *value as f32 * weights.get(stat).cloned().unwrap_or(1_f32))
+            .sum()
+    }
+    /// Gets the arithmetic mean of every defined `Stat`.
+    /// Returns 0.0 if no stat is defined.
+    pub fn average(&self) -> f32 {
+        let values = self.effective_stats();
+        if values.is_empty() {
+            return 0_f32;
+        }
+        values.values().map(|value| *value as f32).sum::<f32>() / values.len() as f32
+    }
+    /// Gets the median of every defined `Stat`, averaging the two middle values
+    /// of an even count. Returns 0.0 if no stat is defined.
+    pub fn median(&self) -> f32 {
+        let mut values: Vec<i32> = self.effective_stats().values().cloned().collect();
+        if values.is_empty() {
+            return 0_f32;
+        }
+        values.sort();
+        let middle = values.len() / 2;
+        if values.len() % 2 == 1 {
+            values[middle] as f32
+        } else {
+            (values[middle - 1] as f32 + values[middle] as f32) / 2_f32
+        }
+    }
+    /// Gets the defined `Stat` with the highest effective value.
+    /// Ties go to the stat that comes first in `Stat` order.
+    /// Returns `None` if no stat is defined.
+    pub fn highest(&self) -> Option<(Stat, i32)> {
+        self.ranked()
+            .into_iter()
+            .min_by_key(|(_, value)| cmp::Reverse(*value))
+    }
+    /// Gets the defined `Stat` with the lowest effective value.
+    /// Ties go to the stat that comes first in `Stat` order.
+    /// Returns `None` if no stat is defined.
+    pub fn lowest(&self) -> Option<(Stat, i32)> {
+        self.ranked().into_iter().min_by_key(|(_, value)| *value)
+    }
+    /// Pairs every defined `Stat` with its effective value, in `Stat` order.
+    fn ranked(&self) -> Vec<(Stat, i32)> {
+        self.defined_stats()
+            .into_iter()
+            .map(|stat| (stat.clone(), self.get_stat(stat)))
+            .collect()
+    }
+    /// Gets a specific `DerivedStat`.
+    /// Returns `None` if no formula is registered for the derived stat.
+    pub fn get_derived(&self, name: DerivedStat) -> Option<i32> {
+        self.derived
+            .get(&name)
+            .map(|formula| saturate(self.rounding.apply((formula.0)(self))))
+    }
+    /// Registers the formula of a specific `DerivedStat`.
+    ///
+    /// # Internals
+    /// Formulas are evaluated on every read, so they should query primary stats
+    /// through `get_stat_f32` to see flat bonuses and multipliers.
+    pub fn add_derived(&mut self, name: DerivedStat, formula: Box<dyn Fn(&CharacterStats) -> f32>) {
+        self.derived.insert(name, Formula(Rc::from(formula)));
+    }
+    /// Gets the base value of a specific `Stat`.
+    /// Flat bonuses and multipliers are not applied.
+    pub fn get_base(&self, stat: Stat) -> Option<f32> {
+        self.base.get(&stat).cloned()
+    }
+    /// Gets the accumulated multiplier of a specific `Stat`.
+    /// Returns 0.0 if no multiplier has been added.
+    ///
+    /// # Internals
+    /// Multipliers are grouped into buckets by priority. Within a bucket they are
+    /// accumulated according to the `StackingMode`, after repeated stacks are weighted
+    /// by the `MultiplierCurve`. Buckets then compound multiplicatively in ascending order.
+    /// Debuffs, global multipliers, multipliers of layers, scaling multipliers and conditional
+    /// multipliers whose condition holds are included as single entries.
+    pub fn get_multiplier(&self, stat: Stat) -> f32 {
+        self.stacking.compound(&self.multiplier_entries(&stat))
+    }
+    /// Explains how a specific `Stat` is computed.
+    ///
+    /// # Panics
+    /// Panics if no base value is defined for the stat.
+    pub fn explain(&self, stat: Stat) -> StatBreakdown {
+        let base = match self.base.get(&stat) {
+            Some(val) => *val,
+            None => panic!("no base value defined for stat"),
+        };
+        StatBreakdown {
+            base,
+            flat: self.get_flat(&stat),
+            multipliers: self
+                .multiplier_entries(&stat)
+                .into_iter()
+                .map(|(source, value, _)| (source.map(str::to_string), value))
+                .collect(),
+            pre_round: self.get_stat_f32(stat.clone()).unwrap_or(base),
+            final_value: self.get_stat(stat),
+        }
+    }
+    /// Gets every active multiplier of a specific `Stat` in insertion order,
+    /// followed by debuffs and conditional multipliers whose condition holds.
+    /// Multipliers of layers are not included.
+    pub fn multipliers_for(&self, stat: Stat) -> Vec<&Multiplier> {
+        let mut mults: Vec<&Multiplier> = match self.multipliers.get(&stat) {
+            Some(mults) => mults.iter().filter(|mult| !mult.is_expired()).collect(),
+            None => Vec::new(),
+        };
+        mults.extend(
+            self.debuffs
+                .iter()
+                .map(|debuff| &debuff.mult)
+                .filter(|mult| mult.stat == stat),
+        );
+        mults.extend(
+            self.conditionals
+                .iter()
+                .filter(|cond| cond.mult.stat == stat && (cond.condition.0)(self))
+                .map(|cond| &cond.mult),
+        );
+        mults
+    }
+    /// Sums the values of every active multiplier of a specific `Stat` tagged with a category,
+    /// such as to break down tooltips by category. Returns 0.0 if none is active.
+    ///
+    /// # Internals
+    /// Values are summed as stored, ignoring the `StackingMode` and `MultiplierCurve`.
+    pub fn multiplier_from_category(&self, stat: Stat, category: &str) -> f32 {
+        self.multipliers_for(stat)
+            .into_iter()
+            .filter(|mult| mult.category.as_deref() == Some(category))
+            .map(|mult| mult.value)
+            .sum()
+    }
+    /// Describes every active multiplier in `Stat::all` and insertion order, followed by debuffs.
+    pub fn active_effects(&self) -> Vec<EffectInfo> {
+        let mults = Stat::all()
+            .iter()
+            .filter_map(|stat| self.multipliers.get(stat))
+            .flat_map(|mults| mults.iter())
+            .filter(|mult| !mult.is_expired());
+        let debuffs = self.debuffs.iter().map(|debuff| &debuff.mult);
+        mults
+            .chain(debuffs)
+            .map(|mult| EffectInfo {
+                stat: mult.stat.clone(),
+                value: mult.value,
+                remaining: mult.duration,
+                source: mult.source.clone(),
+            })
+            .collect()
+    }
+    /// Checks whether multipliers currently change a specific `Stat`.
+    /// Multipliers that cancel each other out are not counted.
+    pub fn has_multiplier(&self, stat: Stat) -> bool {
+        self.get_multiplier(stat) != 0_f32
+    }
+    /// Sets the base value of a specific stat.
+    pub fn set_base(&mut self, stat: Stat, value: f32) {
+        let previous = self.get_base(stat.clone());
+        self.track(|stats| {
+            stats.base.insert(stat.clone(), value);
+        });
+        self.history
+            .record(Operation::SetBase(stat, previous, value));
+    }
+    /// Adds to the base value of a specific stat.
+    /// Undefined stats are treated as zero.
+    pub fn add_base(&mut self, stat: Stat, delta: f32) {
+        self.track(|stats| *stats.base.or_zero(stat) += delta);
+    }
+    /// Forces the effective value of a specific stat, ignoring its base value and modifiers.
+    /// Base values and modifiers are kept and apply again once the override is cleared.
+    /// Every read of the stat reports the override, including `get_stat_f32`,
+    /// so conversions, scaling multipliers and derived stats see it as well.
+    pub fn set_override(&mut self, stat: Stat, value: i32) {
+        self.track(|stats| {
+            stats.overrides.insert(stat, value);
+        });
+    }
+    /// Clears the override of a specific stat.
+    pub fn clear_override(&mut self, stat: Stat) {
+        self.track(|stats| {
+            stats.overrides.remove(&stat);
+        });
+    }
+    /// Regenerates the base value of a specific stat by `per_tick` on every `tick`,
+    /// up to `max`. Base values already above `max` are left untouched.
+    pub fn set_regen(&mut self, stat: Stat, per_tick: f32, max: f32) {
+        self.regen.insert(stat, (per_tick, max));
+    }
+    /// Multiplies every base value by a factor, leaving modifiers untouched.
+    pub fn scale_bases(&mut self, factor: f32) {
+        self.track(|stats| {
+            for stat in Stat::all() {
+                if let Some(value) = stats.base.get_mut(stat) {
+                    *value *= factor;
+                }
+            }
+        });
+    }
+    /// Gets the level. Characters start at level 1.
+    pub fn get_level(&self) -> u32 {
+        self.level
+    }
+    /// Sets the growth of a specific stat per level.
+    pub fn set_growth(&mut self, stat: Stat, per_level: f32) {
+        self.growth.insert(stat, per_level);
+    }
+    /// Sets the level, growing or shrinking base values accordingly.
+    ///
+    /// # Internals
+    /// Every defined stat with a growth rate changes by `growth * levels gained`,
+    /// so starting from level 1 the base becomes `base + growth * (level - 1)`.
+    /// Growth is applied incrementally rather than recomputed from level 1:
+    /// levels gained before a growth rate changes keep the old rate, and base values
+    /// set at a later level are kept as they are.
+    pub fn set_level(&mut self, level: u32) {
+        let levels = level as f32 - self.level as f32;
+        self.track(|stats| {
+            for (stat, per_level) in &stats.growth {
+                if let Some(base) = stats.base.get_mut(stat) {
+                    *base += per_level * levels;
+                }
+            }
+            stats.level = level;
+        });
+    }
+    /// Computes the effective stats as if `set_level` had been called, leaving `self` untouched.
+    pub fn preview_at_level(&self, level: u32) -> HashMap<Stat, i32> {
+        let mut preview = self.clone();
+        preview.set_level(level);
+        preview.effective_stats()
+    }
+    /// Gets the number of unspent stat points.
+    pub fn available_points(&self) -> u32 {
+        self.points
+    }
+    /// Grants unspent stat points.
+    pub fn add_points(&mut self, points: u32) {
+        self.points = self.points.saturating_add(points);
+    }
+    /// Spends stat points to raise the base value of a specific stat.
+    /// Each raise is priced by the `CostCurve`, based on the points already allocated to the stat.
+    ///
+    /// Allocating 0 points does nothing, and leaves an undefined stat undefined.
+    ///
+    /// Returns `StatError::OutOfPoints` if the available points cannot pay for every raise.
+    pub fn allocate(&mut self, stat: Stat, points: u32) -> Result<(), StatError> {
+        if points == 0 {
+            return Ok(());
+        }
+        let allocated = self.allocated.get(&stat).cloned().unwrap_or(0);
+        let cost = (1..=points).fold(0u32, |cost, point| {
+            cost.saturating_add(self.cost_curve.cost(allocated.saturating_add(point)))
+        });
+        if cost > self.points {
+            return Err(StatError::OutOfPoints);
+        }
+        self.track(|stats| {
+            stats.points -= cost;
+            stats.spent = stats.spent.saturating_add(cost);
+            let total = stats.allocated.entry(stat.clone()).or_insert(0);
+            *total = total.saturating_add(points);
+            *stats.base.or_zero(stat) += points as f32;
+        });
+        Ok(())
+    }
+    /// Returns every spent stat point to the pool of unspent points,
+    /// restoring the base values from before the allocation.
+    pub fn reset_points(&mut self) {
+        self.track(|stats| {
+            for (stat, points) in stats.allocated.drain() {
+                if let Some(base) = stats.base.get_mut(&stat) {
+                    *base -= points as f32;
+                }
+            }
+            stats.points = stats.points.saturating_add(stats.spent);
+            stats.spent = 0;
+        });
+    }
+    /// Sets the `CostCurve` used to price allocated stat points.
+    /// Defaults to `CostCurve::Flat`.
+    pub fn set_cost_curve(&mut self, curve: CostCurve) {
+        self.cost_curve = curve;
+    }
+    /// Merges another `CharacterStats` into this one.
+    ///
+    /// # Internals
+    /// Base values and flat bonuses are summed and multipliers are accumulated.
+    /// Stats only defined in `other` are inserted.
+    /// Rounding, stacking and clamps are left untouched,
+    /// and multipliers blocked by an immunity of `self` are skipped.
+    pub fn merge(&mut self, other: &CharacterStats) {
+        self.track(|stats| {
+            for (stat, value) in other.base.iter() {
+                *stats.base.or_zero(stat.clone()) += value;
+            }
+            for (stat, value) in &other.flats {
+                *stats.flats.entry(stat.clone()).or_insert(0f32) += *value;
+            }
+            for (stat, mults) in &other.multipliers {
+                let mults: Vec<Multiplier> = mults
+                    .iter()
+                    .filter(|mult| !stats.is_immune_to(mult))
+                    .cloned()
+                    .collect();
+                stats
+                    .multipliers
+                    .entry(stat.clone())
+                    .or_default()
+                    .extend(mults);
+            }
+        });
+    }
+    /// Freezes the base and effective stats into a read-only view.
+    /// Later changes to `self` are not reflected in the view.
+    pub fn freeze(&self) -> FrozenStats {
+        FrozenStats {
+            base: self.base.to_map(),
+            effective: self.effective_stats(),
+        }
+    }
+    /// Blends two `CharacterStats`, where a `t` of 0.0 yields the base values of `a` and
+    /// 1.0 those of `b`. `t` is clamped to that range.
+    ///
+    /// # Internals
+    /// Stats defined on both sides are interpolated linearly, stats defined on only one side
+    /// keep their base value. Every modifier and setting is taken from `b`.
+    pub fn lerp(a: &CharacterStats, b: &CharacterStats, t: f32) -> CharacterStats {
+        let t = t.clamp(0_f32, 1_f32);
+        let mut stats = b.clone();
+        for (stat, from) in a.base.iter() {
+            let value = match b.base.get(stat) {
+                Some(to) => from + (to - from) * t,
+                None => from,
+            };
+            stats.base.insert(stat.clone(), value);
+        }
+        stats.invalidate_cache();
+        stats
+    }
+    /// Takes a snapshot of the base values and every modifier.
+    /// Change callbacks are not part of the snapshot.
+    pub fn snapshot(&self) -> StatSnapshot {
+        StatSnapshot {
+            stats: self.clone(),
+        }
+    }
+    /// Restores the state captured by `snapshot`.
+    /// Change callbacks and the cache setting of `self` are kept.
+    pub fn restore(&mut self, snapshot: StatSnapshot) {
+        self.track(|stats| {
+            let hooks = mem::take(&mut stats.hooks);
+            let cache = stats.cache.take();
+            *stats = snapshot.stats;
+            stats.hooks = hooks;
+            stats.cache = cache;
+        });
+    }
+    /// Grants a share of the effective value of one stat to another, so a ratio of 0.1_f32
+    /// adds 10% of `from` to `to`. The share is added after the multipliers of both stats.
+    ///
+    /// Returns `StatError::CyclicDependency` if `from` already depends on `to`,
+    /// or `StatError::NonFinite` if the ratio is NaN or infinite.
+    pub fn add_conversion(&mut self, from: Stat, to: Stat, ratio: f32) -> Result<(), StatError> {
+        if !ratio.is_finite() {
+            return Err(StatError::NonFinite);
+        }
+        if self.depends_on(&from, &to) {
+            return Err(StatError::CyclicDependency);
+        }
+        self.track(|stats| stats.conversions.push(Conversion { from, to, ratio }));
+        Ok(())
+    }
+    /// Adds a multiplier to `target` worth `per_point` for every point of the effective value
+    /// of `source`, so a `per_point` of 0.01_f32 grants 1% per point.
+    /// The multiplier is recomputed on every read.
+    ///
+    /// Returns `StatError::CyclicDependency` if `source` already depends on `target`,
+    /// or `StatError::NonFinite` if `per_point` is NaN or infinite.
+    pub fn add_scaling_multiplier(
+        &mut self,
+        target: Stat,
+        per_point: f32,
+        source: Stat,
+    ) -> Result<(), StatError> {
+        if !per_point.is_finite() {
+            return Err(StatError::NonFinite);
+        }
+        if self.depends_on(&source, &target) {
+            return Err(StatError::CyclicDependency);
+        }
+        self.track(|stats| {
+            stats.scalings.push(Scaling {
+                source,
+                target,
+                per_point,
+            })
+        });
+        Ok(())
+    }
+    /// Sets the `RoundingMode` used to compute stats.
+    /// Defaults to `RoundingMode::Round`.
+    pub fn set_rounding(&mut self, mode: RoundingMode) {
+        self.track(|stats| stats.rounding = mode);
+    }
+    /// Sets the lowest value a specific stat can take.
+    ///
+    /// # Internals
+    /// Floors are applied after rounding and leave multipliers untouched,
+    /// so removing a debuff restores the true value.
+    pub fn set_floor(&mut self, stat: Stat, floor: i32) {
+        self.track(|stats| {
+            stats.floors.insert(stat, floor);
+        });
+    }
+    /// Sets the `StackingMode` used to accumulate multipliers.
+    /// Defaults to `StackingMode::Additive`.
+    pub fn set_stacking(&mut self, mode: StackingMode) {
+        self.track(|stats| stats.stacking = mode);
+    }
+    /// Sets the `MultiplierCurve` used to weight repeated stacks.
+    /// Defaults to `MultiplierCurve::Linear`.
+    pub fn set_curve(&mut self, curve: MultiplierCurve) {
+        self.track(|stats| stats.curve = curve);
+    }
+    /// Clamps a specific stat to an optional minimum and maximum.
+    ///
+    /// # Internals
+    /// Clamps are applied after multipliers, but before rounding.
+    pub fn set_clamp(&mut self, stat: Stat, min: Option<f32>, max: Option<f32>) {
+        self.track(|stats| {
+            stats.clamps.insert(stat, (min, max));
+        });
+    }
+    /// Sets a soft cap for a specific stat. The portion of the stat above the cap
+    /// is reduced by a fraction, so a reduction of 0.5_f32 halves it.
+    ///
+    /// # Internals
+    /// Soft caps are applied after multipliers and conversions, but before clamps.
+    pub fn set_soft_cap(&mut self, stat: Stat, cap: f32, reduction: f32) {
+        self.track(|stats| {
+            stats.soft_caps.insert(stat, (cap, reduction));
+        });
+    }
+    /// Limits the magnitude of the accumulated multiplier applied to a specific stat,
+    /// so a cap of 3.0_f32 allows anywhere from -300% to +300%.
+    /// The sign of the cap is ignored, as are NaN caps.
+    ///
+    /// # Internals
+    /// Only the application is capped; `get_multiplier` still reports the full accumulated value.
+    pub fn set_multiplier_cap(&mut self, stat: Stat, cap: f32) {
+        if !cap.is_nan() {
+            self.track(|stats| {
+                stats.multiplier_caps.insert(stat, cap.abs());
+            });
+        }
+    }
+    /// Adds a multiplier for a specific stat.
+    /// Stacks beyond the maximum of the multiplier are ignored,
+    /// as are multipliers the stat is immune to and non-finite values;
+    /// see `try_add_multiplier`.
+    pub fn add_multiplier(&mut self, stat: &Multiplier) {
+        let mut added = false;
+        self.track(|stats| added = stats.push_multiplier(stat));
+        if added {
+            self.history.record(Operation::AddMultiplier(stat.clone()));
         }
     }
-    /// Gets a specific `Stat`.
-    /// Multipliers are applied in the process.
-    pub fn get_stat(&self, stat: Stat) -> i32 {
-        let multiplier = match self.multipliers.get(&stat) {
-            Some(val) => 1_f32 + *val,
-            None => 1_f32,
-        };
-        (self.base.get(&stat).unwrap() * multiplier).round() as i32
+    /// Adds a multiplier for a specific stat.
+    ///
+    /// Returns `StatError::NonFinite` if the value is NaN or infinite.
+    pub fn try_add_multiplier(&mut self, stat: &Multiplier) -> Result<(), StatError> {
+        if !stat.value.is_finite() {
+            return Err(StatError::NonFinite);
+        }
+        self.add_multiplier(stat);
+        Ok(())
+    }
+    /// Adds several multipliers at once.
+    /// Observers are notified once per changed stat, after the whole batch.
+    ///
+    /// # Internals
+    /// Every added multiplier is recorded as a separate `add_multiplier` operation,
+    /// so each one is undone on its own.
+    pub fn add_multipliers(&mut self, mults: &[Multiplier]) {
+        self.track(|stats| {
+            for mult in mults {
+                if stats.push_multiplier(mult) {
+                    stats.history.record(Operation::AddMultiplier(mult.clone()));
+                }
+            }
+        });
+    }
+    /// Removes a multiplier for a specific stat.
+    ///
+    /// # Internals
+    /// Removes the most recently added multiplier with the same value and source.
+    /// Does nothing if no such multiplier exists.
+    pub fn sub_multiplier(&mut self, stat: &Multiplier) {
+        let mut removed = None;
+        self.track(|stats| removed = stats.pop_multiplier(stat));
+        if let Some((mult, index)) = removed {
+            self.history.record(Operation::SubMultiplier(mult, index));
+        }
+    }
+    /// Reverts the last `add_multiplier`, `sub_multiplier` or `set_base` operation.
+    /// Returns whether an operation was reverted.
+    pub fn undo(&mut self) -> bool {
+        let op = match self.history.done.pop() {
+            Some(op) => op,
+            None => return false,
+        };
+        self.track(|stats| match op {
+            Operation::AddMultiplier(ref mult) => {
+                stats.pop_multiplier(mult);
+            }
+            Operation::SubMultiplier(ref mult, index) => {
+                let mults = stats.multipliers.entry(mult.stat.clone()).or_default();
+                let index = index.min(mults.len());
+                mults.insert(index, mult.clone());
+            }
+            Operation::SetBase(ref stat, previous, _) => match previous {
+                Some(value) => {
+                    stats.base.insert(stat.clone(), value);
+                }
+                None => {
+                    stats.base.remove(stat);
+                }
+            },
+        });
+        self.history.undone.push(op);
+        true
+    }
+    /// Reapplies the last operation reverted by `undo`.
+    /// Returns whether an operation was reapplied.
+    ///
+    /// # Internals
+    /// Recording a new operation discards every operation that could be redone.
+    /// Multipliers are reapplied like `add_multiplier`; one that is now blocked by an
+    /// immunity or its stack limit is discarded, and `false` is returned.
+    pub fn redo(&mut self) -> bool {
+        let op = match self.history.undone.pop() {
+            Some(op) => op,
+            None => return false,
+        };
+        let mut applied = true;
+        self.track(|stats| match op {
+            Operation::AddMultiplier(ref mult) => {
+                applied = stats.push_multiplier(mult);
+            }
+            Operation::SubMultiplier(ref mult, _) => {
+                stats.pop_multiplier(mult);
+            }
+            Operation::SetBase(ref stat, _, value) => {
+                stats.base.insert(stat.clone(), value);
+            }
+        });
+        if applied {
+            self.history.done.push(op);
+        }
+        applied
+    }
+    /// Sets how many operations can be undone. Defaults to 100.
+    /// The oldest operations are forgotten first; a depth of 0 disables the history.
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history.depth = depth;
+        self.history.trim();
+    }
+    /// Adds a multiplier applying to every stat, such as a difficulty modifier.
+    /// Non-finite values are ignored.
+    ///
+    /// # Internals
+    /// Global multipliers stack with the multipliers of each stat according to the `StackingMode`.
+    pub fn add_global_multiplier(&mut self, value: f32) {
+        if value.is_finite() {
+            self.track(|stats| stats.globals.push(value));
+        }
+    }
+    /// Removes the most recently added global multiplier with the same value.
+    /// Does nothing if no such multiplier exists.
+    pub fn remove_global_multiplier(&mut self, value: f32) {
+        if let Some(index) = self.globals.iter().rposition(|global| *global == value) {
+            self.track(|stats| {
+                stats.globals.remove(index);
+            });
+        }
+    }
+    /// Replaces every multiplier with a copy of the multipliers of another `CharacterStats`.
+    /// Base values, flat bonuses and every other modifier are left untouched.
+    /// Immunities and stack limits of `self` still apply, see `import_multipliers`.
+    pub fn copy_multipliers_from(&mut self, other: &CharacterStats) {
+        self.import_multipliers(other.export_multipliers());
+    }
+    /// Exports every stored multiplier in `Stat::all` and insertion order.
+    /// Debuffs, layers and conditional multipliers are not included.
+    pub fn export_multipliers(&self) -> MultiplierSet {
+        let multipliers = Stat::all()
+            .iter()
+            .filter_map(|stat| self.multipliers.get(stat))
+            .flat_map(|mults| mults.iter().cloned())
+            .collect();
+        MultiplierSet { multipliers }
+    }
+    /// Replaces every stored multiplier with those of a `MultiplierSet`.
+    ///
+    /// # Internals
+    /// Multipliers are added one by one, so immunities and stack limits still apply.
+    pub fn import_multipliers(&mut self, set: MultiplierSet) {
+        self.track(|stats| {
+            stats.multipliers.clear();
+            for mult in &set.multipliers {
+                stats.push_multiplier(mult);
+            }
+        });
+    }
+    /// Removes every multiplier from a specific source for a specific stat.
+    pub fn remove_multiplier_by_source(&mut self, stat: Stat, source: &str) {
+        self.track(|stats| {
+            if let Some(mults) = stats.multipliers.get_mut(&stat) {
+                mults.retain(|mult| mult.source.as_deref() != Some(source));
+            }
+        });
+    }
+    /// Removes every multiplier from a specific source for a specific stat.
+    ///
+    /// Returns `StatError::UnknownSource` if no multiplier from the source is active.
+    pub fn try_remove_multiplier_by_source(
+        &mut self,
+        stat: Stat,
+        source: &str,
+    ) -> Result<(), StatError> {
+        let found = match self.multipliers.get(&stat) {
+            Some(mults) => mults
+                .iter()
+                .any(|mult| mult.source.as_deref() == Some(source)),
+            None => false,
+        };
+        if !found {
+            return Err(StatError::UnknownSource(source.to_string()));
+        }
+        self.remove_multiplier_by_source(stat, source);
+        Ok(())
+    }
+    /// Removes every multiplier of a specific category for every stat.
+    pub fn remove_category(&mut self, category: &str) {
+        self.track(|stats| {
+            for mults in stats.multipliers.values_mut() {
+                mults.retain(|mult| mult.category.as_deref() != Some(category));
+            }
+        });
+    }
+    /// Adds a `ConditionalMultiplier`.
+    /// Conditional multipliers with a non-finite value are ignored,
+    /// as are those the stat is immune to.
+    pub fn add_conditional(&mut self, cond: ConditionalMultiplier) {
+        if cond.mult.value.is_finite() && !self.is_immune_to(&cond.mult) {
+            self.track(|stats| stats.conditionals.push(cond));
+        }
+    }
+    /// Applies a `Debuff`, replacing any active debuff with the same name.
+    /// Debuffs of stats with an immunity are ignored.
+    pub fn apply_debuff(&mut self, debuff: &Debuff) {
+        if self.is_immune_to(&debuff.mult) {
+            return;
+        }
+        self.track(|stats| {
+            stats.debuffs.retain(|active| active.name != debuff.name);
+            stats.debuffs.push(debuff.clone());
+        });
+    }
+    /// Makes a specific stat immune to multipliers added later,
+    /// or only to negative ones if `negative_only` is set.
+    /// Multipliers that are already active are kept.
+    pub fn set_immunity(&mut self, stat: Stat, negative_only: bool) {
+        self.immunities.insert(stat, negative_only);
+    }
+    /// Checks whether a specific stat has an immunity.
+    pub fn is_immune(&self, stat: Stat) -> bool {
+        self.immunities.contains_key(&stat)
+    }
+    /// Checks whether an immunity blocks a multiplier.
+    fn is_immune_to(&self, mult: &Multiplier) -> bool {
+        match self.immunities.get(&mult.stat) {
+            Some(&negative_only) => !negative_only || mult.value < 0_f32,
+            None => false,
+        }
+    }
+    /// Removes the `Debuff` with a specific name.
+    pub fn remove_debuff(&mut self, name: &str) {
+        self.track(|stats| stats.debuffs.retain(|active| active.name != name));
+    }
+    /// Removes every multiplier for every stat.
+    pub fn clear_multipliers(&mut self) {
+        self.track(|stats| stats.multipliers.clear());
+    }
+    /// Removes every multiplier for a specific stat.
+    pub fn clear_stat_multipliers(&mut self, stat: Stat) {
+        self.track(|stats| {
+            stats.multipliers.remove(&stat);
+        });
+    }
+    /// Removes the stored multipliers of every stat whose multipliers cancel out,
+    /// including stats left without any multiplier.
+    /// Debuffs, layers and conditional multipliers are left untouched.
+    ///
+    /// # Internals
+    /// Stats with a timed or decaying multiplier are kept, since their multipliers
+    /// only cancel out until the next `tick`.
+    pub fn prune(&mut self) {
+        self.track(|stats| {
+            let stacking = &stats.stacking;
+            let curve = &stats.curve;
+            stats.multipliers.retain(|_, mults| {
+                mults
+                    .iter()
+                    .any(|mult| mult.duration.is_some() || mult.decay.is_some())
+                    || stacking.compound(&curve.weigh(mults)).abs() > 1e-6
+            });
+        });
+    }
+    /// Removes every multiplier and flat bonus affecting a specific stat,
+    /// including debuffs, conditional multipliers, conversions, scaling multipliers
+    /// and contributions of layers, as well as its override.
+    /// Clamps, floors and global multipliers are kept.
+    pub fn reset_stat(&mut self, stat: Stat) {
+        self.track(|stats| {
+            stats.overrides.remove(&stat);
+            stats.multipliers.remove(&stat);
+            stats.flats.remove(&stat);
+            stats.debuffs.retain(|debuff| debuff.mult.stat != stat);
+            stats.conditionals.retain(|cond| cond.mult.stat != stat);
+            stats.conversions.retain(|conv| conv.to != stat);
+            stats.scalings.retain(|scaling| scaling.target != stat);
+            for (_, layer) in &mut stats.layers {
+                layer.flats.remove(&stat);
+                layer.multipliers.remove(&stat);
+            }
+        });
+    }
+    /// Advances every timed multiplier, transition and regeneration by one tick.
+    /// Decaying multipliers are weakened, and multipliers whose duration
+    /// runs out or whose value decays away are removed.
+    /// Returns the removed multipliers in `Stat::all` and insertion order.
+    pub fn tick(&mut self) -> Vec<Multiplier> {
+        for transition in self.transitions.values_mut() {
+            transition.elapsed += 1;
+        }
+        self.transitions
+            .retain(|_, transition| transition.elapsed < transition.ticks);
+        let mut expired = Vec::new();
+        self.track(|stats| {
+            for stat in Stat::all() {
+                let mults = match stats.multipliers.get_mut(stat) {
+                    Some(mults) => mults,
+                    None => continue,
+                };
+                for mult in mults.iter_mut() {
+                    if let Some(ref mut duration) = mult.duration {
+                        *duration = duration.saturating_sub(1);
+                    }
+                    if let Some(decay) = mult.decay {
+                        mult.value *= 1_f32 - decay;
+                    }
+                }
+                let (ended, active): (Vec<Multiplier>, Vec<Multiplier>) = mem::take(mults)
+                    .into_iter()
+                    .partition(Multiplier::is_expired);
+                *mults = active;
+                expired.extend(ended);
+            }
+            for (stat, &(per_tick, max)) in &stats.regen {
+                if let Some(base) = stats.base.get_mut(stat) {
+                    if *base < max {
+                        *base = (*base + per_tick).min(max);
+                    }
+                }
+            }
+        });
+        for mult in &expired {
+            for callback in &mut self.hooks.expiry {
+                callback(mult);
+            }
+        }
+        expired
+    }
+    /// Adds a flat bonus for a specific stat.
+    /// Non-finite values are ignored.
+    pub fn add_flat(&mut self, bonus: &FlatBonus) {
+        if bonus.value.is_finite() {
+            self.track(|stats| {
+                *stats.flats.entry(bonus.stat.clone()).or_insert(0f32) += bonus.value
+            });
+        }
+    }
+    /// Removes a flat bonus for a specific stat.
+    /// Non-finite values are ignored.
+    pub fn sub_flat(&mut self, bonus: &FlatBonus) {
+        if bonus.value.is_finite() {
+            self.track(|stats| {
+                *stats.flats.entry(bonus.stat.clone()).or_insert(0f32) -= bonus.value
+            });
+        }
+    }
+    /// Applies an `Effect`.
+    /// Returns the `EffectHandle` used to remove the effect again.
+    ///
+    /// # Internals
+    /// Effects are applied as a `StatLayer`, so both parts are added and removed together.
+    pub fn apply_effect(&mut self, effect: &Effect) -> EffectHandle {
+        EffectHandle(self.push_layer(effect.layer.clone()))
+    }
+    /// Removes an applied `Effect`. Does nothing if the effect was already removed.
+    pub fn remove_effect(&mut self, handle: EffectHandle) {
+        self.remove_layer(handle.0);
+    }
+    /// Adds the same multiplier to each of several stats, such as an aura
+    /// buffing every physical stat. Returns the `MaskHandle` used to remove it again.
+    ///
+    /// # Internals
+    /// Masked multipliers are applied as a `StatLayer`, so they are removed as a unit.
+    /// Non-finite values are ignored, as are stats with an immunity blocking the value.
+    pub fn add_masked_multiplier(&mut self, stats: &[Stat], value: f32) -> MaskHandle {
+        let layer = stats
+            .iter()
+            .filter(|stat| !self.is_immune_to(&Multiplier::new((*stat).clone(), value)))
+            .fold(StatLayer::new(), |layer, stat| {
+                layer.multiplier(stat.clone(), value)
+            });
+        MaskHandle(self.push_layer(layer))
+    }
+    /// Removes a masked multiplier. Does nothing if it was already removed.
+    pub fn remove_masked(&mut self, handle: MaskHandle) {
+        self.remove_layer(handle.0);
+    }
+    /// Pushes a `StatLayer` on top of the base values.
+    /// Returns the `LayerId` used to remove the layer again.
+    pub fn push_layer(&mut self, layer: StatLayer) -> LayerId {
+        let id = LayerId(self.next_layer);
+        self.next_layer += 1;
+        self.track(|stats| stats.layers.push((id, layer)));
+        id
+    }
+    /// Removes a `StatLayer`, returning it if it was active.
+    pub fn remove_layer(&mut self, id: LayerId) -> Option<StatLayer> {
+        let index = self
+            .layers
+            .iter()
+            .position(|&(layer_id, _)| layer_id == id)?;
+        let mut removed = None;
+        self.track(|stats| removed = Some(stats.layers.remove(index).1));
+        removed
+    }
+    /// Encodes base values, flat bonuses and multipliers into a compact binary format.
+    ///
+    /// # Internals
+    /// Each section is prefixed with its entry count and stats are encoded as single bytes.
+    /// Entries are written in `Stat::all` order, so equal states encode identically.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_stat_map(&mut out, &self.base.to_map());
+        write_stat_map(&mut out, &self.flats);
+        let mults: Vec<&Multiplier> = Stat::all()
+            .iter()
+            .filter_map(|stat| self.multipliers.get(stat))
+            .flat_map(|mults| mults.iter())
+            .collect();
+        write_u32(&mut out, mults.len() as u32);
+        for mult in mults {
+            out.push(mult.stat.to_byte());
+            out.extend_from_slice(&mult.value.to_le_bytes());
+            write_string(&mut out, &mult.source);
+            write_u32_option(&mut out, mult.duration);
+            write_string(&mut out, &mult.category);
+            write_u32_option(&mut out, mult.max_stacks);
+            write_u32(&mut out, mult.priority as u32);
+            write_u32_option(&mut out, mult.decay.map(f32::to_bits));
+        }
+        out
+    }
+    /// Decodes `CharacterStats` encoded by `to_bytes`.
+    ///
+    /// Returns `StatError::MalformedData` if the data is truncated or malformed,
+    /// or holds a non-finite multiplier value or a decay outside the range from 0.0 to 1.0.
+    pub fn from_bytes(data: &[u8]) -> Result<CharacterStats, StatError> {
+        let mut reader = ByteReader { data, pos: 0 };
+        let mut stats = CharacterStats::new(reader.read_stat_map()?);
+        stats.flats = reader.read_stat_map()?;
+        for _ in 0..reader.read_u32()? {
+            let mult = Multiplier {
+                stat: reader.read_stat()?,
+                value: reader.read_f32()?,
+                source: reader.read_string()?,
+                duration: reader.read_u32_option()?,
+                category: reader.read_string()?,
+                max_stacks: reader.read_u32_option()?,
+                priority: reader.read_u32()? as i32,
+                decay: reader.read_u32_option()?.map(f32::from_bits),
+            };
+            let decay_valid = mult
+                .decay
+                .is_none_or(|decay| (0_f32..=1_f32).contains(&decay));
+            if !mult.value.is_finite() || !decay_valid {
+                return Err(StatError::MalformedData);
+            }
+            stats
+                .multipliers
+                .entry(mult.stat.clone())
+                .or_default()
+                .push(mult);
+        }
+        if reader.pos != data.len() {
+            return Err(StatError::MalformedData);
+        }
+        Ok(stats)
+    }
+    /// Summarizes every defined `Stat` in `Stat::all` order, one line per stat,
+    /// such as "Strength: 11 (base 10, +10%)". Non-zero flat bonuses are listed as well.
+    pub fn summary(&self) -> String {
+        self.base
+            .iter()
+            .map(|(stat, base)| {
+                // Round to a tenth of a percent, like `Multiplier` does.
+                let percentage = (self.get_multiplier(stat.clone()) * 1000_f32).round() / 10_f32;
+                let flat = self.get_flat(stat);
+                let flat = if flat == 0_f32 {
+                    String::new()
+                } else {
+                    format!(", {:+} flat", flat)
+                };
+                format!(
+                    "{}: {} (base {}{}, {:+}%)",
+                    stat,
+                    self.get_stat(stat.clone()),
+                    base,
+                    flat,
+                    percentage
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+    /// Exports every defined `Stat` as pairs of its `Display` name and effective value,
+    /// in `Stat::all` order.
+    pub fn to_kv(&self) -> Vec<(String, f32)> {
+        Stat::all()
+            .iter()
+            .filter(|stat| self.base.contains_key(stat))
+            .map(|stat| (stat.to_string(), self.get_stat(stat.clone()) as f32))
+            .collect()
+    }
+    /// Constructs new `CharacterStats` from pairs of stat names and base values,
+    /// such as the ones exported by `to_kv`.
+    ///
+    /// Returns `StatError::UnknownStat` if a key does not name a stat.
+    pub fn from_kv(pairs: &[(String, f32)]) -> Result<CharacterStats, StatError> {
+        let mut base = HashMap::new();
+        for (key, value) in pairs {
+            base.insert(key.parse::<Stat>()?, *value);
+        }
+        Ok(CharacterStats::new(base))
+    }
+    /// Registers a callback invoked whenever the effective value of a stat changes.
+    ///
+    /// # Internals
+    /// The callback receives the stat and its new effective value.
+    /// Callbacks are not carried over to clones.
+    pub fn on_change(&mut self, callback: Box<dyn FnMut(Stat, i32)>) {
+        self.hooks.observers.push(callback);
+    }
+    /// Registers a callback invoked for every multiplier that `tick` removes,
+    /// because its duration ran out or its value decayed away.
+    /// Callbacks are not carried over to clones.
+    pub fn on_expire(&mut self, callback: Box<dyn FnMut(&Multiplier)>) {
+        self.hooks.expiry.push(callback);
+    }
+    /// Registers a callback invoked whenever the effective value of a stat crosses a threshold.
+    ///
+    /// # Internals
+    /// The callback receives `true` when the stat rises to or above the threshold,
+    /// and `false` when it drops back below. Undefined stats count as below.
+    /// Like observers, thresholds follow the target value and skip active transitions.
+    pub fn add_threshold(&mut self, stat: Stat, value: i32, on_cross: Box<dyn FnMut(bool)>) {
+        let reached = self.target_stat(stat.clone()).is_ok_and(|v| v >= value);
+        self.hooks.thresholds.push(Threshold {
+            stat,
+            value,
+            reached,
+            on_cross,
+        });
+    }
+    /// Gets the contribution of every active multiplier of a stat, labeled by its source
+    /// and priority. Repeated stacks are weighted by the `MultiplierCurve`.
+    fn multiplier_entries(&self, stat: &Stat) -> Vec<MultiplierEntry<'_>> {
+        let mut entries = match lookup(&self.multipliers, stat) {
+            Some(mults) => self.curve.weigh(mults),
+            None => Vec::new(),
+        };
+        for debuff in &self.debuffs {
+            if debuff.mult.stat == *stat {
+                entries.push((
+                    Some(debuff.name.as_str()),
+                    debuff.mult.value,
+                    debuff.mult.priority,
+                ));
+            }
+        }
+        for value in &self.globals {
+            entries.push((None, *value, 0));
+        }
+        for (_, layer) in &self.layers {
+            if let Some(value) = layer.multipliers.get(stat) {
+                entries.push((None, *value, 0));
+            }
+        }
+        for cond in &self.conditionals {
+            if cond.mult.stat == *stat && (cond.condition.0)(self) {
+                entries.push((
+                    cond.mult.source.as_deref(),
+                    cond.mult.value,
+                    cond.mult.priority,
+                ));
+            }
+        }
+        for scaling in self
+            .scalings
+            .iter()
+            .filter(|scaling| scaling.target == *stat)
+        {
+            let points = self.get_stat_f32(scaling.source.clone()).unwrap_or(0_f32);
+            entries.push((None, scaling.per_point * points, 0));
+        }
+        entries
+    }
+    /// Checks whether every effective stat is at least that of another `CharacterStats`,
+    /// and at least one is strictly greater.
+    ///
+    /// # Internals
+    /// A stat without a base value counts as lower than any defined stat,
+    /// so `self` never dominates an `other` that defines a stat `self` lacks.
+    pub fn dominates(&self, other: &CharacterStats) -> bool {
+        let mut greater = false;
+        for stat in Stat::all() {
+            let ours = self.try_get_stat(stat.clone()).ok();
+            let theirs = other.try_get_stat(stat.clone()).ok();
+            if ours < theirs {
+                return false;
+            }
+            greater |= ours > theirs;
+        }
+        greater
+    }
+    /// Checks whether base values, flat bonuses and accumulated multipliers
+    /// all lie within `epsilon` of those of another `CharacterStats`.
+    pub fn approx_eq(&self, other: &CharacterStats, epsilon: f32) -> bool {
+        let close = |a: f32, b: f32| (a - b).abs() <= epsilon;
+        Stat::all().iter().all(|stat| {
+            let base = match (self.base.get(stat), other.base.get(stat)) {
+                (Some(a), Some(b)) => close(*a, *b),
+                (None, None) => true,
+                _ => false,
+            };
+            base && close(self.get_flat(stat), other.get_flat(stat))
+                && close(
+                    self.get_multiplier(stat.clone()),
+                    other.get_multiplier(stat.clone()),
+                )
+        })
+    }
+    /// Gets the summed flat bonus of a specific stat, including layers.
+    fn get_flat(&self, stat: &Stat) -> f32 {
+        let flat = match lookup(&self.flats, stat) {
+            Some(val) => *val,
+            None => 0_f32,
+        };
+        let layers: f32 = self
+            .layers
+            .iter()
+            .filter_map(|(_, layer)| layer.flats.get(stat))
+            .sum();
+        flat + layers
+    }
+    /// Gets the bit patterns of the base value, modifiers and limits of a stat.
+    /// Zero modifiers are normalized, so an absent modifier equals one that was added and removed.
+    fn fingerprint(&self, stat: &Stat) -> Fingerprint {
+        let normalize = |value: f32| if value == 0_f32 { 0_f32 } else { value };
+        Fingerprint {
+            base: self.base.get(stat).map(|val| val.to_bits()),
+            flat: normalize(self.get_flat(stat)).to_bits(),
+            multiplier: normalize(self.get_multiplier(stat.clone())).to_bits(),
+            conversions: self
+                .conversions
+                .iter()
+                .filter(|conv| conv.to == *stat)
+                .map(|conv| (conv.from.clone(), conv.ratio.to_bits()))
+                .collect(),
+            multiplier_cap: self.multiplier_caps.get(stat).map(|cap| cap.to_bits()),
+            soft_cap: self
+                .soft_caps
+                .get(stat)
+                .map(|&(cap, reduction)| (cap.to_bits(), reduction.to_bits())),
+            clamp: self
+                .clamps
+                .get(stat)
+                .map(|&(min, max)| (min.map(|min| min.to_bits()), max.map(|max| max.to_bits()))),
+            floor: self.floors.get(stat).cloned(),
+            override_value: self.overrides.get(stat).cloned(),
+        }
+    }
+    /// Adds a multiplier without notifying observers.
+    /// Returns whether the multiplier was added.
+    fn push_multiplier(&mut self, stat: &Multiplier) -> bool {
+        if !stat.value.is_finite() || self.is_immune_to(stat) {
+            return false;
+        }
+        let mults = self.multipliers.entry(stat.stat.clone()).or_default();
+        if let Some(max_stacks) = stat.max_stacks {
+            let stacks = mults
+                .iter()
+                .filter(|mult| !mult.is_expired() && mult.is_stack_of(stat))
+                .count();
+            if stacks >= max_stacks as usize {
+                return false;
+            }
+        }
+        mults.push(stat.clone());
+        true
+    }
+    /// Removes the most recently added stack of a multiplier without notifying observers.
+    /// Returns the removed multiplier and its position.
+    fn pop_multiplier(&mut self, stat: &Multiplier) -> Option<(Multiplier, usize)> {
+        let mults = self.multipliers.get_mut(&stat.stat)?;
+        let index = mults.iter().rposition(|mult| mult.is_stack_of(stat))?;
+        Some((mults.remove(index), index))
+    }
+    /// Gets every defined `Stat`, ignoring active transitions.
+    fn target_stats(&self) -> HashMap<Stat, i32> {
+        self.base
+            .keys()
+            .filter_map(|stat| Some((stat.clone(), self.target_stat(stat.clone()).ok()?)))
+            .collect()
+    }
+    /// Starts transitions of stats whose unrounded value differs from the value before a mutation.
+    fn start_transitions(&mut self, pending: Vec<(Stat, f32, f32)>) {
+        for (stat, shown, before) in pending {
+            let ticks = match self.transition_ticks.get(&stat) {
+                Some(&ticks) => ticks,
+                None => continue,
+            };
+            if self
+                .get_stat_f32(stat.clone())
+                .is_some_and(|after| after != before)
+            {
+                let transition = Transition {
+                    from: shown,
+                    elapsed: 0,
+                    ticks,
+                };
+                self.transitions.insert(stat, transition);
+            }
+        }
+    }
+    /// Gets the stats whose effective value feeds into a specific stat.
+    fn dependencies(&self, stat: &Stat) -> Vec<&Stat> {
+        let conversions = self
+            .conversions
+            .iter()
+            .filter(|conv| conv.to == *stat)
+            .map(|conv| &conv.from);
+        let scalings = self
+            .scalings
+            .iter()
+            .filter(|scaling| scaling.target == *stat)
+            .map(|scaling| &scaling.source);
+        conversions.chain(scalings).collect()
+    }
+    /// Checks whether a stat is, directly or transitively, computed from another one.
+    /// Every stat depends on itself.
+    fn depends_on(&self, stat: &Stat, other: &Stat) -> bool {
+        let mut visited: Vec<&Stat> = Vec::new();
+        let mut pending = vec![stat];
+        while let Some(current) = pending.pop() {
+            if current == other {
+                return true;
+            }
+            if !visited.contains(&current) {
+                visited.push(current);
+                pending.extend(self.dependencies(current));
+            }
+        }
+        false
+    }
+    /// Discards cached effective stats.
+    fn invalidate_cache(&self) {
+        if let Some(ref cache) = self.cache {
+            cache.borrow_mut().clear();
+        }
+    }
+    /// Applies a mutation, notifying observers of every stat whose effective value changed
+    /// and firing thresholds that were crossed.
+    /// Stats with a configured transition start interpolating if their value changed.
+    fn track<F: FnOnce(&mut CharacterStats)>(&mut self, mutate: F) {
+        let pending: Vec<(Stat, f32, f32)> = self
+            .transition_ticks
+            .keys()
+            .filter_map(|stat| {
+                let shown = self.displayed_f32(stat)?;
+                Some((stat.clone(), shown, self.get_stat_f32(stat.clone())?))
+            })
+            .collect();
+        if self.hooks.observers.is_empty() && self.hooks.thresholds.is_empty() {
+            mutate(self);
+            self.invalidate_cache();
+            self.start_transitions(pending);
+            return;
+        }
+        let before = self.target_stats();
+        mutate(self);
+        self.invalidate_cache();
+        self.start_transitions(pending);
+        let after = self.target_stats();
+        for stat in Stat::all() {
+            if let Some(&value) = after.get(stat) {
+                if before.get(stat) != Some(&value) {
+                    for observer in &mut self.hooks.observers {
+                        observer(stat.clone(), value);
+                    }
+                }
+            }
+        }
+        for threshold in &mut self.hooks.thresholds {
+            let reached = after
+                .get(&threshold.stat)
+                .is_some_and(|&value| value >= threshold.value);
+            if reached != threshold.reached {
+                threshold.reached = reached;
+                (threshold.on_cross)(reached);
+            }
+        }
+    }
+}
+
+impl Default for CharacterStats {
+    /// Constructs `CharacterStats` with every `Stat` at zero.
+    fn default() -> Self {
+        CharacterStats::new(
+            Stat::all()
+                .iter()
+                .map(|stat| (stat.clone(), 0f32))
+                .collect(),
+        )
+    }
+}
+
+impl FromIterator<(Stat, f32)> for CharacterStats {
+    /// Collects `(Stat, base)` pairs into `CharacterStats` without any multipliers.
+    fn from_iter<I: IntoIterator<Item = (Stat, f32)>>(iter: I) -> Self {
+        CharacterStats::new(iter.into_iter().collect())
+    }
+}
+
+impl Add for CharacterStats {
+    type Output = CharacterStats;
+    /// Combines two `CharacterStats`, see `CharacterStats::merge`.
+    fn add(mut self, other: CharacterStats) -> CharacterStats {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for CharacterStats {
+    fn add_assign(&mut self, other: CharacterStats) {
+        self.merge(&other);
+    }
+}
+
+impl Sub for CharacterStats {
+    type Output = CharacterStats;
+    /// Reverts combining two `CharacterStats`.
+    fn sub(mut self, other: CharacterStats) -> CharacterStats {
+        self -= other;
+        self
+    }
+}
+
+impl SubAssign for CharacterStats {
+    /// Subtracts base values and flat bonuses, and removes one stack
+    /// of every multiplier of `other`.
+    /// Stats only defined in `other` are inserted with negated values.
+    fn sub_assign(&mut self, other: CharacterStats) {
+        self.track(|stats| {
+            for (stat, value) in other.base.iter() {
+                *stats.base.or_zero(stat.clone()) -= value;
+            }
+            for (stat, value) in &other.flats {
+                *stats.flats.entry(stat.clone()).or_insert(0f32) -= *value;
+            }
+            for mult in other.multipliers.values().flat_map(|mults| mults.iter()) {
+                stats.pop_multiplier(mult);
+            }
+        });
+    }
+}
+
+impl PartialEq for CharacterStats {
+    /// Compares the configuration that determines effective stats: base values, flat bonuses,
+    /// accumulated multipliers, conversions, caps, clamps, floors, overrides and the rounding mode.
+    ///
+    /// # Internals
+    /// Values are compared by their exact bits, so values that merely round to
+    /// the same effective stat are not equal. Use `approx_eq` for tolerant comparison.
+    /// Active transitions, immunities and callbacks are not compared.
+    fn eq(&self, other: &Self) -> bool {
+        self.rounding == other.rounding
+            && Stat::all()
+                .iter()
+                .all(|stat| self.fingerprint(stat) == other.fingerprint(stat))
+    }
+}
+
+/// Equality is total, because values are compared by their bits:
+/// NaN equals itself, while `0.0` and `-0.0` base values differ.
+impl Eq for CharacterStats {}
+
+impl Hash for CharacterStats {
+    /// Hashes the same bit patterns compared by `PartialEq`, in `Stat::all` order.
+    /// The effective-stats cache is not hashed.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rounding.hash(state);
+        for stat in Stat::all() {
+            self.fingerprint(stat).hash(state);
+        }
+    }
+}
+
+impl CharacterStatsBuilder {
+    /// Constructs a new `CharacterStatsBuilder`.
+    pub fn new() -> Self {
+        CharacterStatsBuilder::default()
+    }
+    /// Sets the base value of a specific stat.
+    pub fn base(mut self, stat: Stat, value: f32) -> Self {
+        self.base.insert(stat, value);
+        self
+    }
+    /// Adds a multiplier for a specific stat.
+    pub fn multiplier(mut self, stat: Stat, value: f32) -> Self {
+        self.multipliers.push(Multiplier::new(stat, value));
+        self
+    }
+    /// Builds the `CharacterStats`.
+    pub fn build(self) -> CharacterStats {
+        let mut stats = CharacterStats::new(self.base);
+        for mult in &self.multipliers {
+            stats.add_multiplier(mult);
+        }
+        stats
+    }
+}
+
+impl StatLayer {
+    /// Constructs a new, empty `StatLayer`.
+    pub fn new() -> Self {
+        StatLayer::default()
+    }
+    /// Adds a flat bonus for a specific stat.
+    /// Non-finite values are ignored.
+    pub fn flat(mut self, stat: Stat, value: f32) -> Self {
+        if value.is_finite() {
+            *self.flats.entry(stat).or_insert(0f32) += value;
+        }
+        self
+    }
+    /// Adds a multiplier for a specific stat.
+    /// Non-finite values are ignored.
+    pub fn multiplier(mut self, stat: Stat, value: f32) -> Self {
+        if value.is_finite() {
+            *self.multipliers.entry(stat).or_insert(0f32) += value;
+        }
+        self
+    }
+}
+
+impl FrozenStats {
+    /// Gets a specific `Stat` as it was when frozen.
+    ///
+    /// # Panics
+    /// Panics if no base value was defined for the stat.
+    pub fn get_stat(&self, stat: Stat) -> i32 {
+        match self.effective.get(&stat) {
+            Some(value) => *value,
+            None => panic!("no base value defined for stat"),
+        }
+    }
+    /// Gets the base value of a specific `Stat` as it was when frozen.
+    pub fn get_base(&self, stat: Stat) -> Option<f32> {
+        self.base.get(&stat).cloned()
+    }
+    /// Gets every defined `Stat` as it was when frozen.
+    pub fn effective_stats(&self) -> HashMap<Stat, i32> {
+        self.effective.clone()
+    }
+}
+
+impl Effect {
+    /// Constructs a new, empty `Effect`.
+    pub fn new() -> Self {
+        Effect::default()
+    }
+    /// Adds a flat bonus for a specific stat.
+    /// Non-finite values are ignored.
+    pub fn flat(mut self, stat: Stat, value: f32) -> Self {
+        self.layer = self.layer.flat(stat, value);
+        self
+    }
+    /// Adds a percentage bonus for a specific stat, so 0.1_f32 increases it by 10%.
+    /// Non-finite values are ignored.
+    pub fn percent(mut self, stat: Stat, value: f32) -> Self {
+        self.layer = self.layer.multiplier(stat, value);
+        self
+    }
+}
+
+impl Stat {
+    /// Number of stats.
+    pub const COUNT: usize = 7;
+    /// Gets the position of the `Stat` in `Stat::all`.
+    pub fn index(&self) -> usize {
+        match *self {
+            Stat::Str => 0,
+            Stat::Int => 1,
+            Stat::Swi => 2,
+            Stat::Dex => 3,
+            Stat::Vit => 4,
+            Stat::Luck => 5,
+            Stat::Cha => 6,
+        }
+    }
+    /// Gets every `Stat` in declaration order.
+    pub fn all() -> &'static [Stat] {
+        &[
+            Stat::Str,
+            Stat::Int,
+            Stat::Swi,
+            Stat::Dex,
+            Stat::Vit,
+            Stat::Luck,
+            Stat::Cha,
+        ]
+    }
+}
+
+impl Stat {
+    /// Encodes the `Stat` as its position in `Stat::all`.
+    fn to_byte(&self) -> u8 {
+        self.index() as u8
+    }
+    /// Decodes a `Stat` encoded by `to_byte`.
+    fn from_byte(byte: u8) -> Option<Stat> {
+        Stat::all().get(byte as usize).cloned()
+    }
+}
+
+impl fmt::Display for Stat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Stat::Str => "Strength",
+            Stat::Int => "Intelligence",
+            Stat::Swi => "Swiftness",
+            Stat::Dex => "Dexterity",
+            Stat::Vit => "Vitality",
+            Stat::Luck => "Luck",
+            Stat::Cha => "Charisma",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for StatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StatError::MissingBase(ref stat) => write!(f, "no base value defined for {}", stat),
+            StatError::UnknownStat(ref name) => write!(f, "unknown stat \"{}\"", name),
+            StatError::OutOfPoints => write!(f, "not enough stat points available"),
+            StatError::MalformedData => write!(f, "malformed stat data"),
+            StatError::PositiveDebuff => write!(f, "debuff value must not be positive"),
+            StatError::NonFinite => write!(f, "multiplier value must be finite"),
+            StatError::UnknownSource(ref source) => {
+                write!(f, "no multiplier from source \"{}\"", source)
+            }
+            StatError::CyclicDependency => write!(f, "stat would depend on itself"),
+            StatError::PercentOutOfRange => write!(f, "percentage must not be below -100%"),
+            StatError::InvalidNotation(ref notation) => {
+                write!(f, "invalid dice notation \"{}\"", notation)
+            }
+        }
+    }
+}
+
+impl error::Error for StatError {}
+
+impl FromStr for Stat {
+    type Err = StatError;
+    /// Parses a `Stat` from its short code or full name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "str" | "strength" => Ok(Stat::Str),
+            "int" | "intelligence" => Ok(Stat::Int),
+            "swi" | "swiftness" => Ok(Stat::Swi),
+            "dex" | "dexterity" => Ok(Stat::Dex),
+            "vit" | "vitality" => Ok(Stat::Vit),
+            "luck" => Ok(Stat::Luck),
+            "cha" | "charisma" => Ok(Stat::Cha),
+            _ => Err(StatError::UnknownStat(s.to_string())),
+        }
+    }
+}
+
+impl RoundingMode {
+    /// Rounds a value according to the mode.
+    pub fn apply(&self, value: f32) -> f32 {
+        match *self {
+            RoundingMode::Round => value.round(),
+            RoundingMode::Floor => value.floor(),
+            RoundingMode::Ceil => value.ceil(),
+            RoundingMode::Truncate => value.trunc(),
+        }
+    }
+}
+
+impl StackingMode {
+    /// Accumulates multiplier values according to the mode.
+    pub fn combine<I: Iterator<Item = f32>>(&self, values: I) -> f32 {
+        match *self {
+            StackingMode::Additive => values.sum(),
+            StackingMode::Multiplicative => {
+                values.fold(1_f32, |acc, val| acc * (1_f32 + val)) - 1_f32
+            }
+        }
+    }
+    /// Combines the entries of each priority bucket, then compounds the buckets
+    /// multiplicatively in ascending order.
+    fn compound(&self, entries: &[MultiplierEntry]) -> f32 {
+        let mut priorities: Vec<i32> = entries.iter().map(|&(_, _, priority)| priority).collect();
+        priorities.sort();
+        priorities.dedup();
+        priorities
+            .iter()
+            .map(|bucket| {
+                self.combine(
+                    entries
+                        .iter()
+                        .filter(|&&(_, _, priority)| priority == *bucket)
+                        .map(|&(_, value, _)| value),
+                )
+            })
+            .fold(1_f32, |acc, bucket| acc * (1_f32 + bucket))
+            - 1_f32
+    }
+}
+
+impl MultiplierCurve {
+    /// Gets the weight of the nth stack, counting from 1.
+    pub fn weight(&self, stack: usize) -> f32 {
+        match *self {
+            MultiplierCurve::Linear => 1_f32,
+            MultiplierCurve::DiminishingHarmonic => 1_f32 / stack as f32,
+            MultiplierCurve::Logarithmic => 1_f32 / (1_f32 + (stack as f32).ln()),
+        }
+    }
+    /// Weighs every active multiplier by its stack among the previous ones.
+    fn weigh<'a>(&self, mults: &'a [Multiplier]) -> Vec<MultiplierEntry<'a>> {
+        let active: Vec<&Multiplier> = mults.iter().filter(|mult| !mult.is_expired()).collect();
+        active
+            .iter()
+            .enumerate()
+            .map(|(index, mult)| {
+                let stack = active[..index]
+                    .iter()
+                    .filter(|prev| prev.is_stack_of(mult))
+                    .count()
+                    + 1;
+                (
+                    mult.source.as_deref(),
+                    mult.value * self.weight(stack),
+                    mult.priority,
+                )
+            })
+            .collect()
+    }
+}
+
+impl CostCurve {
+    /// Gets the cost of the nth point allocated to a stat, counting from 1.
+    pub fn cost(&self, point: u32) -> u32 {
+        match *self {
+            CostCurve::Flat => 1,
+            CostCurve::Linear => point,
+            CostCurve::Quadratic => point.saturating_mul(point),
+        }
+    }
+}
+
+impl Multiplier {
+    /// Constructs a new `Multiplier`.
+    ///
+    /// # Internals
+    /// Values are percentages, so a value of 0.1_f32 increases the specified stat by 10%.
+    pub fn new(stat: Stat, value: f32) -> Self {
+        Multiplier {
+            stat,
+            value,
+            source: None,
+            duration: None,
+            category: None,
+            max_stacks: None,
+            priority: 0,
+            decay: None,
+        }
+    }
+    /// Constructs a new `Multiplier`.
+    ///
+    /// Returns `StatError::NonFinite` if the value is NaN or infinite.
+    pub fn try_new(stat: Stat, value: f32) -> Result<Self, StatError> {
+        if value.is_finite() {
+            Ok(Multiplier::new(stat, value))
+        } else {
+            Err(StatError::NonFinite)
+        }
+    }
+    /// Constructs a new `Multiplier` from a range-checked `Percent`.
+    pub fn from_percent(stat: Stat, value: Percent) -> Self {
+        Multiplier::new(stat, value.value())
+    }
+    /// Gets the value of the `Multiplier` as a percentage, so 0.15_f32 yields 15.0.
+    pub fn percentage(&self) -> f32 {
+        self.value * 100_f32
+    }
+    /// Tags the `Multiplier` with a source, such as the item granting it.
+    ///
+    /// # Internals
+    /// Sourced multipliers can be removed with `CharacterStats::remove_multiplier_by_source`.
+    pub fn with_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+    /// Limits the `Multiplier` to a number of ticks.
+    ///
+    /// # Internals
+    /// Timed multipliers are removed by `CharacterStats::tick` once their duration runs out.
+    pub fn with_duration(mut self, ticks: u32) -> Self {
+        self.duration = Some(ticks);
+        self
+    }
+    /// Tags the `Multiplier` with a category, such as "aura" or "spell".
+    ///
+    /// # Internals
+    /// Every multiplier of a category can be removed with `CharacterStats::remove_category`.
+    pub fn with_category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+    /// Limits how many stacks of the `Multiplier` can be active at once.
+    ///
+    /// # Internals
+    /// Multipliers with the same value and source count as stacks of each other.
+    /// `CharacterStats::add_multiplier` ignores stacks beyond the limit.
+    pub fn with_max_stacks(mut self, max_stacks: u32) -> Self {
+        self.max_stacks = Some(max_stacks);
+        self
+    }
+    /// Sets the priority bucket of the `Multiplier`.
+    ///
+    /// # Internals
+    /// Multipliers of the same priority stack according to the `StackingMode`,
+    /// while buckets of different priorities compound multiplicatively in ascending order.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+    /// Makes the `Multiplier` lose a fraction of its value every tick,
+    /// so a decay of 0.5_f32 halves it each time.
+    /// Fractions are clamped to the range from 0.0 to 1.0, and NaN is ignored.
+    ///
+    /// # Internals
+    /// Decaying multipliers are weakened by `CharacterStats::tick`
+    /// and removed once their value is negligible.
+    pub fn with_decay(mut self, fraction: f32) -> Self {
+        if !fraction.is_nan() {
+            self.decay = Some(fraction.clamp(0_f32, 1_f32));
+        }
+        self
+    }
+    /// Checks whether another `Multiplier` is a stack of the same buff.
+    fn is_stack_of(&self, other: &Multiplier) -> bool {
+        self.value == other.value && self.source == other.source
+    }
+    /// Checks whether the duration of the `Multiplier` has run out,
+    /// or its value has decayed below 0.01%.
+    fn is_expired(&self) -> bool {
+        self.duration == Some(0) || (self.decay.is_some() && self.value.abs() < 1e-4)
+    }
+}
+
+impl fmt::Display for Multiplier {
+    /// Formats the `Multiplier` as a signed percentage, such as "Strength +15%".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Round to a tenth of a percent to hide floating-point noise.
+        let percentage = (self.percentage() * 10_f32).round() / 10_f32;
+        write!(f, "{} {:+}%", self.stat, percentage)
+    }
+}
+
+impl Mul<f32> for Multiplier {
+    type Output = Multiplier;
+    /// Scales the value of the `Multiplier`, so a +10% times 2.0 yields +20%.
+    fn mul(mut self, factor: f32) -> Multiplier {
+        self.value *= factor;
+        self
+    }
+}
+
+impl Add for Multiplier {
+    type Output = Multiplier;
+    /// Sums the values of two multipliers, keeping every other property of `self`.
+    ///
+    /// # Panics
+    /// Panics if the multipliers are for different stats.
+    fn add(mut self, other: Multiplier) -> Multiplier {
+        if self.stat != other.stat {
+            panic!("cannot add multipliers of different stats");
+        }
+        self.value += other.value;
+        self
+    }
+}
+
+impl Percent {
+    /// Constructs a new `Percent`, where 0.1_f32 stands for 10%.
+    ///
+    /// Returns `StatError::PercentOutOfRange` if the value is below -1.0,
+    /// or `StatError::NonFinite` if it is NaN or infinite.
+    pub fn new(value: f32) -> Result<Percent, StatError> {
+        if !value.is_finite() {
+            return Err(StatError::NonFinite);
+        }
+        if value < -1_f32 {
+            return Err(StatError::PercentOutOfRange);
+        }
+        Ok(Percent(value))
+    }
+    /// Gets the wrapped value.
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+}
+
+impl ConditionalMultiplier {
+    /// Constructs a new `ConditionalMultiplier`.
+    ///
+    /// # Internals
+    /// The condition is evaluated on every read of the stat, so it must not
+    /// query the stat the multiplier applies to.
+    pub fn new(mult: Multiplier, condition: Box<dyn Fn(&CharacterStats) -> bool>) -> Self {
+        ConditionalMultiplier {
+            mult,
+            condition: Condition(Rc::from(condition)),
+        }
+    }
+}
+
+impl Debuff {
+    /// Constructs a new `Debuff`.
+    ///
+    /// Returns `StatError::PositiveDebuff` if the value is positive,
+    /// so a value of -0.2_f32 reduces the specified stat by 20%,
+    /// or `StatError::NonFinite` if it is NaN or infinite.
+    pub fn new(name: &str, stat: Stat, value: f32) -> Result<Self, StatError> {
+        if !value.is_finite() {
+            return Err(StatError::NonFinite);
+        }
+        if value > 0_f32 {
+            return Err(StatError::PositiveDebuff);
+        }
+        Ok(Debuff {
+            name: name.to_string(),
+            mult: Multiplier::new(stat, value).with_source(name),
+        })
+    }
+}
+
+impl FlatBonus {
+    /// Constructs a new `FlatBonus`.
+    ///
+    /// # Internals
+    /// Flat bonuses are added to the base value before any multipliers are applied.
+    pub fn new(stat: Stat, value: f32) -> Self {
+        FlatBonus { stat, value }
+    }
+}
+impl fmt::Debug for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Formula")
+    }
+}
+
+impl fmt::Debug for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Condition")
+    }
+}
+
+impl Clone for Hooks {
+    /// Callbacks are not cloned, so the clone starts without any.
+    fn clone(&self) -> Self {
+        Hooks::default()
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Hooks({} observers, {} thresholds, {} expiry callbacks)",
+            self.observers.len(),
+            self.thresholds.len(),
+            self.expiry.len()
+        )
+    }
+}
+
+impl History {
+    /// Records an applied operation, discarding the redo stack.
+    fn record(&mut self, op: Operation) {
+        self.undone.clear();
+        self.done.push(op);
+        self.trim();
+    }
+    /// Forgets the oldest operations beyond the depth.
+    fn trim(&mut self) {
+        if self.done.len() > self.depth {
+            let excess = self.done.len() - self.depth;
+            self.done.drain(..excess);
+        }
+    }
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            undone: Vec::new(),
+            done: Vec::new(),
+            depth: 100,
+        }
+    }
+}
+
+impl BaseStore {
+    fn get(&self, stat: &Stat) -> Option<&f32> {
+        match *self {
+            BaseStore::Map(ref map) => map.get(stat),
+            BaseStore::Array(ref slots) => slots[stat.index()].as_ref(),
+        }
+    }
+    fn get_mut(&mut self, stat: &Stat) -> Option<&mut f32> {
+        match *self {
+            BaseStore::Map(ref mut map) => map.get_mut(stat),
+            BaseStore::Array(ref mut slots) => slots[stat.index()].as_mut(),
+        }
+    }
+    fn insert(&mut self, stat: Stat, value: f32) {
+        match *self {
+            BaseStore::Map(ref mut map) => {
+                map.insert(stat, value);
+            }
+            BaseStore::Array(ref mut slots) => slots[stat.index()] = Some(value),
+        }
+    }
+    fn remove(&mut self, stat: &Stat) {
+        match *self {
+            BaseStore::Map(ref mut map) => {
+                map.remove(stat);
+            }
+            BaseStore::Array(ref mut slots) => slots[stat.index()] = None,
+        }
+    }
+    fn contains_key(&self, stat: &Stat) -> bool {
+        self.get(stat).is_some()
+    }
+    /// Gets the base value of a stat, inserting zero if it is undefined.
+    fn or_zero(&mut self, stat: Stat) -> &mut f32 {
+        match *self {
+            BaseStore::Map(ref mut map) => map.entry(stat).or_insert(0f32),
+            BaseStore::Array(ref mut slots) => slots[stat.index()].get_or_insert(0f32),
+        }
+    }
+    /// Iterates over defined stats and their base values in `Stat::all` order.
+    fn iter(&self) -> impl Iterator<Item = (&'static Stat, f32)> + '_ {
+        Stat::all()
+            .iter()
+            .filter_map(move |stat| self.get(stat).map(|value| (stat, *value)))
+    }
+    fn keys(&self) -> impl Iterator<Item = &'static Stat> + '_ {
+        self.iter().map(|(stat, _)| stat)
+    }
+    fn to_map(&self) -> HashMap<Stat, f32> {
+        self.iter()
+            .map(|(stat, value)| (stat.clone(), value))
+            .collect()
+    }
+}
+
+impl From<HashMap<Stat, f32>> for BaseStore {
+    fn from(map: HashMap<Stat, f32>) -> Self {
+        BaseStore::Map(map)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<BaseStore> for BTreeMap<Stat, f32> {
+    fn from(base: BaseStore) -> Self {
+        base.iter()
+            .map(|(stat, value)| (stat.clone(), value))
+            .collect()
+    }
+}
+
+/// Cursor over binary data decoded by `CharacterStats::from_bytes`.
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    /// Reads the next `len` bytes.
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StatError> {
+        if self.data.len() - self.pos < len {
+            return Err(StatError::MalformedData);
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+    fn read_u8(&mut self) -> Result<u8, StatError> {
+        Ok(self.take(1)?[0])
+    }
+    fn read_u32(&mut self) -> Result<u32, StatError> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+    fn read_f32(&mut self) -> Result<f32, StatError> {
+        self.read_u32().map(f32::from_bits)
+    }
+    fn read_stat(&mut self) -> Result<Stat, StatError> {
+        Stat::from_byte(self.read_u8()?).ok_or(StatError::MalformedData)
+    }
+    fn read_stat_map(&mut self) -> Result<HashMap<Stat, f32>, StatError> {
+        let mut map = HashMap::new();
+        for _ in 0..self.read_u32()? {
+            let stat = self.read_stat()?;
+            map.insert(stat, self.read_f32()?);
+        }
+        Ok(map)
+    }
+    fn read_present(&mut self) -> Result<bool, StatError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(StatError::MalformedData),
+        }
+    }
+    fn read_u32_option(&mut self) -> Result<Option<u32>, StatError> {
+        if self.read_present()? {
+            self.read_u32().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    fn read_string(&mut self) -> Result<Option<String>, StatError> {
+        if !self.read_present()? {
+            return Ok(None);
+        }
+        let len = self.read_u32()? as usize;
+        match String::from_utf8(self.take(len)?.to_vec()) {
+            Ok(string) => Ok(Some(string)),
+            Err(_) => Err(StatError::MalformedData),
+        }
+    }
+}
+
+fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_stat_map(out: &mut Vec<u8>, map: &HashMap<Stat, f32>) {
+    let entries: Vec<(&Stat, f32)> = Stat::all()
+        .iter()
+        .filter_map(|stat| map.get(stat).map(|value| (stat, *value)))
+        .collect();
+    write_u32(out, entries.len() as u32);
+    for (stat, value) in entries {
+        out.push(stat.to_byte());
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn write_u32_option(out: &mut Vec<u8>, value: Option<u32>) {
+    match value {
+        Some(value) => {
+            out.push(1);
+            write_u32(out, value);
+        }
+        None => out.push(0),
+    }
+}
+
+fn write_string(out: &mut Vec<u8>, value: &Option<String>) {
+    match *value {
+        Some(ref value) => {
+            out.push(1);
+            write_u32(out, value.len() as u32);
+            out.extend_from_slice(value.as_bytes());
+        }
+        None => out.push(0),
+    }
+}
+
+/// Most dice accepted by `CharacterStats::roll_from_notation`.
+#[cfg(feature = "rand")]
+const MAX_DICE: u32 = 100;
+
+/// Most sides per die accepted by `CharacterStats::roll_from_notation`.
+#[cfg(feature = "rand")]
+const MAX_SIDES: usize = 1000;
+
+/// Gets the entry of a stat, skipping the hash entirely if the map is empty.
+/// Keeps reads of stats without modifiers cheap.
+fn lookup<'a, V>(map: &'a HashMap<Stat, V>, stat: &Stat) -> Option<&'a V> {
+    if map.is_empty() {
+        None
+    } else {
+        map.get(stat)
+    }
+}
+
+/// Gets the level characters start at.
+fn first_level() -> u32 {
+    1
+}
+
+/// Serializes a map ordered by stat, so equal states serialize identically.
+#[cfg(feature = "serde")]
+fn serialize_ordered<V, S>(map: &HashMap<Stat, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    V: Serialize,
+    S: Serializer,
+{
+    let ordered: BTreeMap<&Stat, &V> = map.iter().collect();
+    ordered.serialize(serializer)
+}
+
+/// Converts a rounded value to `i32`, saturating at the bounds.
+fn saturate(value: f32) -> i32 {
+    if value >= i32::MAX as f32 {
+        i32::MAX
+    } else if value <= i32::MIN as f32 {
+        i32::MIN
+    } else {
+        value as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    macro_rules! hashmap {
+        ($($key:expr => $val:expr),*) => {{
+            let mut map = ::std::collections::HashMap::new();
+            $( map.insert($key, $val); )*
+            map
+        }};
+    }
+    fn mock_base_stats() -> CharacterStats {
+        CharacterStats::new(hashmap![
+            Stat::Str => 10_f32,
+            Stat::Int => 10_f32,
+            Stat::Swi => 10_f32
+        ])
+    }
+    #[test]
+    fn add_multiplier() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        stats.add_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+    }
+    #[test]
+    fn add_multipliers() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        for _ in 0..7 {
+            stats.add_multiplier(&mult);
+        }
+        assert_eq!(stats.get_stat(Stat::Str), 17);
+    }
+    #[test]
+    fn add_negative_multiplier() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, -0.1_f32);
+        stats.add_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 9);
+    }
+    #[test]
+    fn add_negative_multipliers() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, -0.1_f32);
+        for _ in 0..7 {
+            stats.add_multiplier(&mult);
+        }
+        assert_eq!(stats.get_stat(Stat::Str), 3);
+    }
+    #[test]
+    fn add_remove_multiplier() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        stats.add_multiplier(&mult);
+        stats.sub_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn add_remove_multipliers() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        for _ in 0..7 {
+            stats.add_multiplier(&mult);
+        }
+        for _ in 0..7 {
+            stats.sub_multiplier(&mult);
+        }
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn add_remove_negative_multiplier() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, -0.1_f32);
+        stats.add_multiplier(&mult);
+        stats.sub_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn add_remove_negative_multipliers() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, -0.1_f32);
+        for _ in 0..7 {
+            stats.add_multiplier(&mult);
+        }
+        for _ in 0..7 {
+            stats.sub_multiplier(&mult);
+        }
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn get_without_multipliers() {
+        let stats = mock_base_stats();
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn try_get_stat() {
+        let stats = mock_base_stats();
+        assert!(stats.try_get_stat(Stat::Str) == Ok(10));
+    }
+    #[test]
+    fn try_get_missing_stat() {
+        let stats = CharacterStats::new(hashmap![Stat::Str => 10_f32]);
+        assert!(stats.try_get_stat(Stat::Int) == Err(StatError::MissingBase(Stat::Int)));
+    }
+    #[test]
+    #[should_panic]
+    fn get_missing_stat() {
+        let stats = CharacterStats::new(hashmap![Stat::Str => 10_f32]);
+        stats.get_stat(Stat::Int);
+    }
+    #[test]
+    fn add_flat() {
+        let mut stats = mock_base_stats();
+        stats.add_flat(&FlatBonus::new(Stat::Str, 5_f32));
+        assert_eq!(stats.get_stat(Stat::Str), 15);
+    }
+    #[test]
+    fn add_remove_flat() {
+        let mut stats = mock_base_stats();
+        let flat = FlatBonus::new(Stat::Str, 5_f32);
+        stats.add_flat(&flat);
+        stats.sub_flat(&flat);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn add_flat_and_multiplier() {
+        let mut stats = mock_base_stats();
+        stats.add_flat(&FlatBonus::new(Stat::Str, 5_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        // (10 + 5) * 1.1 = 16.5
+        assert_eq!(stats.get_stat(Stat::Str), 17);
+    }
+    #[test]
+    fn get_base() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        assert!(stats.get_base(Stat::Str) == Some(10_f32));
+    }
+    #[test]
+    fn get_missing_base() {
+        let stats = CharacterStats::new(hashmap![Stat::Str => 10_f32]);
+        assert!(stats.get_base(Stat::Int).is_none());
+    }
+    #[test]
+    fn get_multiplier() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        for _ in 0..3 {
+            stats.add_multiplier(&mult);
+        }
+        assert!((stats.get_multiplier(Stat::Str) - 0.3_f32).abs() < 1e-6);
+    }
+    #[test]
+    fn get_multiplier_without_multipliers() {
+        let stats = mock_base_stats();
+        assert!(stats.get_multiplier(Stat::Str) == 0_f32);
+    }
+    #[test]
+    fn effective_stats() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        let effective = stats.effective_stats();
+        assert_eq!(effective.len(), 3);
+        assert_eq!(effective[&Stat::Str], 11);
+        assert_eq!(effective[&Stat::Int], 10);
+        assert_eq!(effective[&Stat::Swi], 10);
+    }
+    #[test]
+    fn effective_stats_only_defined() {
+        let stats = CharacterStats::new(hashmap![Stat::Str => 10_f32]);
+        let effective = stats.effective_stats();
+        assert_eq!(effective.len(), 1);
+        assert!(!effective.contains_key(&Stat::Int));
+    }
+    #[test]
+    fn clone_stats() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        let clone = stats.clone();
+        assert_eq!(clone.effective_stats(), stats.effective_stats());
+    }
+    #[test]
+    fn rounding_modes() {
+        let mut stats = CharacterStats::new(hashmap![
+            Stat::Str => 10.5_f32,
+            Stat::Int => -10.5_f32
+        ]);
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+        assert_eq!(stats.get_stat(Stat::Int), -11);
+        stats.set_rounding(RoundingMode::Floor);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Int), -11);
+        stats.set_rounding(RoundingMode::Ceil);
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+        assert_eq!(stats.get_stat(Stat::Int), -10);
+        stats.set_rounding(RoundingMode::Truncate);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Int), -10);
+    }
+    #[test]
+    fn rounding_mode_with_multiplier() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.05_f32));
+        stats.set_rounding(RoundingMode::Floor);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn get_stat_f32() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.15_f32));
+        assert!(stats.get_stat_f32(Stat::Str) == Some(11.5_f32));
+    }
+    #[test]
+    fn get_missing_stat_f32() {
+        let stats = CharacterStats::new(hashmap![Stat::Str => 10_f32]);
+        assert!(stats.get_stat_f32(Stat::Int).is_none());
+    }
+    #[test]
+    fn clamp_min() {
+        let mut stats = mock_base_stats();
+        stats.set_clamp(Stat::Str, Some(1_f32), None);
+        let mult = Multiplier::new(Stat::Str, -0.5_f32);
+        for _ in 0..3 {
+            stats.add_multiplier(&mult);
+        }
+        assert_eq!(stats.get_stat(Stat::Str), 1);
+    }
+    #[test]
+    fn clamp_max() {
+        let mut stats = mock_base_stats();
+        stats.set_clamp(Stat::Str, None, Some(50_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 100_f32));
+        assert_eq!(stats.get_stat(Stat::Str), 50);
+    }
+    #[test]
+    fn extended_stats() {
+        let stats = CharacterStats::new(hashmap![
+            Stat::Dex => 11_f32,
+            Stat::Vit => 12_f32,
+            Stat::Luck => 13_f32,
+            Stat::Cha => 14_f32
+        ]);
+        assert_eq!(stats.get_stat(Stat::Dex), 11);
+        assert_eq!(stats.get_stat(Stat::Vit), 12);
+        assert_eq!(stats.get_stat(Stat::Luck), 13);
+        assert_eq!(stats.get_stat(Stat::Cha), 14);
+    }
+    #[test]
+    fn all_stats() {
+        let all = Stat::all();
+        assert_eq!(all.len(), 7);
+        assert!(all.contains(&Stat::Str));
+        assert_eq!(all[0], Stat::Str);
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_stat_name() {
+        assert_eq!(::serde_json::to_string(&Stat::Str).unwrap(), "\"Str\"");
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        let json = ::serde_json::to_string(&stats).unwrap();
+        let restored: CharacterStats = ::serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.effective_stats(), stats.effective_stats());
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_base_only() {
+        let json = r#"{"base":{"Str":10.0,"Int":8.0}}"#;
+        let stats: CharacterStats = ::serde_json::from_str(json).unwrap();
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Int), 8);
+    }
+    #[test]
+    fn remove_multiplier_by_source() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("amulet"));
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+        stats.remove_multiplier_by_source(Stat::Str, "ring");
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+    }
+    #[test]
+    fn sub_multiplier_matches_source() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        stats.sub_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+        stats.sub_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn timed_multiplier() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.2_f32).with_duration(2));
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+        stats.tick();
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+        stats.tick();
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn tick_keeps_permanent_multiplier() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.2_f32));
+        stats.tick();
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+    }
+    #[test]
+    fn expired_multiplier_ignored() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.2_f32).with_duration(0));
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn builder() {
+        let stats = CharacterStatsBuilder::new()
+            .base(Stat::Str, 10_f32)
+            .base(Stat::Int, 8_f32)
+            .base(Stat::Swi, 6_f32)
+            .multiplier(Stat::Str, 0.2_f32)
+            .build();
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+        assert_eq!(stats.get_stat(Stat::Int), 8);
+        assert_eq!(stats.get_stat(Stat::Swi), 6);
+    }
+    #[test]
+    fn clear_multipliers() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.2_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Int, -0.2_f32));
+        stats.clear_multipliers();
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Int), 10);
+    }
+    #[test]
+    fn clear_stat_multipliers() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.2_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Int, -0.2_f32));
+        stats.clear_stat_multipliers(Stat::Str);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Int), 8);
+    }
+    #[test]
+    fn set_base() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        stats.set_base(Stat::Str, 15_f32);
+        // 15 * 1.1 = 16.5
+        assert_eq!(stats.get_stat(Stat::Str), 17);
+    }
+    #[test]
+    fn add_base() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 10_f32]);
+        stats.add_base(Stat::Str, 5_f32);
+        stats.add_base(Stat::Int, 3_f32);
+        assert!(stats.get_base(Stat::Str) == Some(15_f32));
+        assert!(stats.get_base(Stat::Int) == Some(3_f32));
+    }
+    #[test]
+    fn additive_stacking() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        stats.add_multiplier(&mult);
+        stats.add_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 120);
+    }
+    #[test]
+    fn multiplicative_stacking() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        stats.set_stacking(StackingMode::Multiplicative);
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        stats.add_multiplier(&mult);
+        stats.add_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 121);
+    }
+    #[test]
+    fn display_stat() {
+        assert_eq!(format!("{}", Stat::Int), "Intelligence");
+        assert_eq!(Stat::Str.to_string(), "Strength");
+    }
+    #[test]
+    fn parse_stat_short_code() {
+        assert_eq!("str".parse::<Stat>(), Ok(Stat::Str));
+    }
+    #[test]
+    fn parse_stat_full_name() {
+        assert_eq!("InTelLigence".parse::<Stat>(), Ok(Stat::Int));
+    }
+    #[test]
+    fn parse_stat_display_round_trip() {
+        for stat in Stat::all() {
+            assert_eq!(stat.to_string().parse::<Stat>().as_ref(), Ok(stat));
+        }
+    }
+    #[test]
+    fn parse_unknown_stat() {
+        assert_eq!(
+            "wisdom".parse::<Stat>(),
+            Err(StatError::UnknownStat("wisdom".to_string()))
+        );
+    }
+    #[test]
+    fn merge() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 5_f32]);
+        let mut other = mock_base_stats();
+        other.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        stats.merge(&other);
+        assert!(stats.get_base(Stat::Str) == Some(15_f32));
+        assert!(stats.get_base(Stat::Int) == Some(10_f32));
+        // 15 * 1.1 = 16.5
+        assert_eq!(stats.get_stat(Stat::Str), 17);
+    }
+    #[test]
+    fn snapshot_restore() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        let snapshot = stats.snapshot();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        stats.set_base(Stat::Int, 20_f32);
+        assert_eq!(stats.get_stat(Stat::Str), 16);
+        stats.restore(snapshot);
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+        assert_eq!(stats.get_stat(Stat::Int), 10);
+    }
+    #[test]
+    fn saturate_overflow() {
+        let mut stats = CharacterStats::new(hashmap![
+            Stat::Str => 1e10_f32,
+            Stat::Int => -1e10_f32
+        ]);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 10_f32));
+        assert_eq!(stats.get_stat(Stat::Str), i32::MAX);
+        assert_eq!(stats.get_stat(Stat::Int), i32::MIN);
+    }
+    #[test]
+    fn derived_stat() {
+        let mut stats = CharacterStats::new(hashmap![
+            Stat::Str => 10_f32,
+            Stat::Vit => 10_f32
+        ]);
+        stats.add_derived(
+            DerivedStat::Hp,
+            Box::new(|stats: &CharacterStats| {
+                let vit = stats.get_stat_f32(Stat::Vit).unwrap_or(0_f32);
+                let str = stats.get_stat_f32(Stat::Str).unwrap_or(0_f32);
+                vit * 10_f32 + str * 2_f32
+            }),
+        );
+        assert_eq!(stats.get_derived(DerivedStat::Hp), Some(120));
+        stats.add_multiplier(&Multiplier::new(Stat::Vit, 0.1_f32));
+        assert_eq!(stats.get_derived(DerivedStat::Hp), Some(130));
+    }
+    #[test]
+    fn missing_derived_stat() {
+        let stats = mock_base_stats();
+        assert_eq!(stats.get_derived(DerivedStat::Mp), None);
+    }
+    #[test]
+    fn on_change() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let mut stats = mock_base_stats();
+        let sink = changes.clone();
+        stats.on_change(Box::new(move |stat, value| {
+            sink.borrow_mut().push((stat, value))
+        }));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        assert_eq!(*changes.borrow(), vec![(Stat::Str, 11)]);
+    }
+    #[test]
+    fn on_change_multiple_observers() {
+        let count = Rc::new(RefCell::new(0));
+        let mut stats = mock_base_stats();
+        for _ in 0..2 {
+            let count = count.clone();
+            stats.on_change(Box::new(move |_, _| *count.borrow_mut() += 1));
+        }
+        stats.set_base(Stat::Int, 12_f32);
+        stats.sub_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        assert_eq!(*count.borrow(), 2);
+    }
+    #[test]
+    fn restore_keeps_observers() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let mut stats = mock_base_stats();
+        let snapshot = stats.snapshot();
+        let sink = changes.clone();
+        stats.on_change(Box::new(move |stat, value| {
+            sink.borrow_mut().push((stat, value))
+        }));
+        stats.set_base(Stat::Str, 20_f32);
+        stats.restore(snapshot);
+        stats.set_base(Stat::Int, 5_f32);
+        assert_eq!(
+            *changes.borrow(),
+            vec![(Stat::Str, 20), (Stat::Str, 10), (Stat::Int, 5)]
+        );
+    }
+    #[test]
+    fn eq_stats() {
+        let mut a = mock_base_stats();
+        let mut b = mock_base_stats();
+        a.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        b.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        assert!(a == b);
+    }
+    #[test]
+    fn ne_stats_multiplier() {
+        let mut a = mock_base_stats();
+        let mut b = mock_base_stats();
+        a.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        b.add_multiplier(&Multiplier::new(Stat::Str, 0.2_f32));
+        assert!(a != b);
+    }
+    #[test]
+    fn eq_stats_removed_multiplier() {
+        let mut a = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        a.add_multiplier(&mult);
+        a.sub_multiplier(&mult);
+        assert!(a == mock_base_stats());
+    }
+    #[test]
+    fn ne_stats_configuration() {
+        use std::collections::hash_map::DefaultHasher;
+        let hash_of = |stats: &CharacterStats| {
+            let mut hasher = DefaultHasher::new();
+            stats.hash(&mut hasher);
+            hasher.finish()
+        };
+        let configure: Vec<fn(&mut CharacterStats)> = vec![
+            |stats| stats.set_override(Stat::Str, 1),
+            |stats| stats.set_clamp(Stat::Str, None, Some(5_f32)),
+            |stats| stats.set_floor(Stat::Str, 12),
+            |stats| stats.set_soft_cap(Stat::Str, 5_f32, 0.5_f32),
+            |stats| stats.set_multiplier_cap(Stat::Str, 0.5_f32),
+            |stats| stats.set_rounding(RoundingMode::Floor),
+            |stats| stats.add_conversion(Stat::Int, Stat::Str, 0.5_f32).unwrap(),
+        ];
+        for configure in configure {
+            let mut stats = mock_base_stats();
+            configure(&mut stats);
+            assert!(stats != mock_base_stats());
+            let mut again = mock_base_stats();
+            configure(&mut again);
+            assert!(stats == again);
+            assert_eq!(hash_of(&stats), hash_of(&again));
+        }
+    }
+    #[test]
+    fn approx_eq_stats() {
+        let mut a = mock_base_stats();
+        let mut b = mock_base_stats();
+        a.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        b.add_multiplier(&Multiplier::new(Stat::Str, 0.1000001_f32));
+        assert!(a != b);
+        assert!(a.approx_eq(&b, 1e-5));
+        assert!(!a.approx_eq(&mock_base_stats(), 1e-5));
+    }
+    #[test]
+    fn remove_category() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_category("aura"));
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 0.1_f32).with_category("aura"));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.2_f32).with_category("spell"));
+        stats.remove_category("aura");
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+        assert_eq!(stats.get_stat(Stat::Int), 10);
+    }
+    #[test]
+    fn max_stacks() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        let mult = Multiplier::new(Stat::Str, 0.05_f32)
+            .with_source("poison")
+            .with_max_stacks(3);
+        for _ in 0..6 {
+            stats.add_multiplier(&mult);
+        }
+        assert_eq!(stats.get_stat(Stat::Str), 115);
+    }
+    #[test]
+    fn max_stacks_after_removal() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        let mult = Multiplier::new(Stat::Str, 0.05_f32).with_max_stacks(1);
+        stats.add_multiplier(&mult);
+        stats.sub_multiplier(&mult);
+        stats.add_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 105);
+    }
+    #[test]
+    fn default_stats() {
+        let stats = CharacterStats::default();
+        for stat in Stat::all() {
+            assert_eq!(stats.get_stat(stat.clone()), 0);
+        }
+    }
+    #[test]
+    fn has_no_multiplier() {
+        let stats = mock_base_stats();
+        assert!(!stats.has_multiplier(Stat::Str));
+    }
+    #[test]
+    fn has_positive_multiplier() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        assert!(stats.has_multiplier(Stat::Str));
+        assert!(!stats.has_multiplier(Stat::Int));
+    }
+    #[test]
+    fn has_net_zero_multiplier() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        stats.add_multiplier(&mult);
+        stats.sub_multiplier(&mult);
+        assert!(!stats.has_multiplier(Stat::Str));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, -0.1_f32).with_source("curse"));
+        assert!(!stats.has_multiplier(Stat::Str));
+    }
+    #[test]
+    fn display_positive_multiplier() {
+        let mult = Multiplier::new(Stat::Str, 0.15_f32);
+        assert_eq!(mult.to_string(), "Strength +15%");
+    }
+    #[test]
+    fn display_negative_multiplier() {
+        let mult = Multiplier::new(Stat::Swi, -0.1_f32);
+        assert_eq!(mult.to_string(), "Swiftness -10%");
+    }
+    #[test]
+    fn display_fractional_multiplier() {
+        let mult = Multiplier::new(Stat::Int, 0.075_f32);
+        assert_eq!(mult.to_string(), "Intelligence +7.5%");
+    }
+    #[test]
+    fn harmonic_curve() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        stats.set_curve(MultiplierCurve::DiminishingHarmonic);
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        for _ in 0..5 {
+            stats.add_multiplier(&mult);
+        }
+        // 10% * (1 + 1/2 + 1/3 + 1/4 + 1/5) = 22.8%
+        assert!(stats.get_multiplier(Stat::Str) < 0.5_f32);
+        assert_eq!(stats.get_stat(Stat::Str), 123);
+    }
+    #[test]
+    fn logarithmic_curve() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        stats.set_curve(MultiplierCurve::Logarithmic);
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        for _ in 0..5 {
+            stats.add_multiplier(&mult);
+        }
+        let total = stats.get_multiplier(Stat::Str);
+        assert!(total > 0.228_f32 && total < 0.5_f32);
+    }
+    #[test]
+    fn curve_only_weights_stacks() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        stats.set_curve(MultiplierCurve::DiminishingHarmonic);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("amulet"));
+        assert_eq!(stats.get_stat(Stat::Str), 120);
+    }
+    #[test]
+    fn dominates() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        assert!(stats.dominates(&mock_base_stats()));
+        assert!(!mock_base_stats().dominates(&stats));
+    }
+    #[test]
+    fn equal_does_not_dominate() {
+        assert!(!mock_base_stats().dominates(&mock_base_stats()));
+    }
+    #[test]
+    fn incomparable_does_not_dominate() {
+        let mut a = mock_base_stats();
+        let mut b = mock_base_stats();
+        a.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        b.add_multiplier(&Multiplier::new(Stat::Int, 0.1_f32));
+        assert!(!a.dominates(&b));
+        assert!(!b.dominates(&a));
+    }
+    #[test]
+    fn missing_stat_does_not_dominate() {
+        let a = CharacterStats::new(hashmap![Stat::Str => 20_f32]);
+        let b = CharacterStats::new(hashmap![Stat::Str => 10_f32, Stat::Int => 1_f32]);
+        assert!(!a.dominates(&b));
+        assert!(mock_base_stats().dominates(&CharacterStats::new(hashmap![Stat::Str => 10_f32])));
+    }
+    #[test]
+    fn total() {
+        let mut stats = mock_base_stats();
+        assert_eq!(stats.total(), 30);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        assert_eq!(stats.total(), 31);
+    }
+    #[test]
+    fn total_weighted() {
+        let stats = mock_base_stats();
+        let weights = hashmap![Stat::Str => 2_f32];
+        assert!(stats.total_weighted(&weights) == 40_f32);
+    }
+    #[test]
+    fn add_multipliers_batch() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let mut stats = mock_base_stats();
+        let sink = changes.clone();
+        stats.on_change(Box::new(move |stat, value| {
+            sink.borrow_mut().push((stat, value))
+        }));
+        stats.add_multipliers(&[
+            Multiplier::new(Stat::Str, 0.1_f32),
+            Multiplier::new(Stat::Str, 0.2_f32),
+            Multiplier::new(Stat::Int, 0.5_f32),
+        ]);
+        assert_eq!(stats.get_stat(Stat::Str), 13);
+        assert_eq!(stats.get_stat(Stat::Int), 15);
+        assert_eq!(stats.get_stat(Stat::Swi), 10);
+        assert_eq!(*changes.borrow(), vec![(Stat::Str, 13), (Stat::Int, 15)]);
+    }
+    #[test]
+    fn collect_stats() {
+        let stats: CharacterStats = vec![(Stat::Str, 10_f32), (Stat::Int, 8_f32)]
+            .into_iter()
+            .collect();
+        assert!(stats.get_base(Stat::Str) == Some(10_f32));
+        assert!(stats.get_base(Stat::Int) == Some(8_f32));
+        assert!(stats.get_base(Stat::Swi).is_none());
+    }
+    #[test]
+    fn allocate_points() {
+        let mut stats = mock_base_stats();
+        stats.add_points(5);
+        assert!(stats.allocate(Stat::Str, 3).is_ok());
+        assert_eq!(stats.available_points(), 2);
+        assert_eq!(stats.get_stat(Stat::Str), 13);
+    }
+    #[test]
+    fn allocate_edge_cases() {
+        let mut stats = mock_base_stats();
+        stats.add_points(5);
+        assert!(stats.allocate(Stat::Luck, 0).is_ok());
+        assert_eq!(stats.get_base(Stat::Luck), None);
+        stats.spent = u32::MAX - 1;
+        assert!(stats.allocate(Stat::Str, 3).is_ok());
+        assert_eq!(stats.spent, u32::MAX);
+        assert_eq!(stats.get_stat(Stat::Str), 13);
+    }
+    #[test]
+    fn allocate_too_many_points() {
+        let mut stats = mock_base_stats();
+        stats.add_points(2);
+        assert_eq!(stats.allocate(Stat::Str, 3), Err(StatError::OutOfPoints));
+        assert_eq!(stats.available_points(), 2);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn reset_points() {
+        let mut stats = mock_base_stats();
+        stats.add_points(5);
+        stats.allocate(Stat::Str, 3).unwrap();
+        stats.allocate(Stat::Int, 2).unwrap();
+        stats.reset_points();
+        assert_eq!(stats.available_points(), 5);
+        assert!(stats.get_base(Stat::Str) == Some(10_f32));
+        assert!(stats.get_base(Stat::Int) == Some(10_f32));
+    }
+    #[test]
+    fn floor() {
+        let mut stats = mock_base_stats();
+        stats.set_floor(Stat::Str, 1);
+        let debuff = Multiplier::new(Stat::Str, -1.2_f32);
+        stats.add_multiplier(&debuff);
+        assert_eq!(stats.get_stat(Stat::Str), 1);
+        assert!((stats.get_multiplier(Stat::Str) + 1.2_f32).abs() < 1e-6);
+        stats.sub_multiplier(&debuff);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn push_remove_layer() {
+        let mut stats = mock_base_stats();
+        let weapon = StatLayer::new().flat(Stat::Str, 5_f32);
+        let id = stats.push_layer(weapon);
+        assert_eq!(stats.get_stat(Stat::Str), 15);
+        assert!(stats.remove_layer(id).is_some());
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert!(stats.remove_layer(id).is_none());
+    }
+    #[test]
+    fn layer_multiplier() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        let armor = StatLayer::new()
+            .flat(Stat::Str, 10_f32)
+            .multiplier(Stat::Str, 0.4_f32);
+        stats.push_layer(armor);
+        // (10 + 10) * 1.5 = 30
+        assert_eq!(stats.get_stat(Stat::Str), 30);
+    }
+    #[test]
+    fn bytes_round_trip() {
+        let mut stats = mock_base_stats();
+        stats.add_flat(&FlatBonus::new(Stat::Int, 2_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        stats.add_multiplier(
+            &Multiplier::new(Stat::Swi, -0.2_f32)
+                .with_duration(3)
+                .with_category("curse"),
+        );
+        let restored = CharacterStats::from_bytes(&stats.to_bytes()).unwrap();
+        assert_eq!(restored.effective_stats(), stats.effective_stats());
+        assert!(restored == stats);
+    }
+    #[test]
+    fn bytes_truncated() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        let bytes = stats.to_bytes();
+        for len in 0..bytes.len() {
+            assert_eq!(
+                CharacterStats::from_bytes(&bytes[..len]).err(),
+                Some(StatError::MalformedData)
+            );
+        }
+    }
+    #[test]
+    fn bytes_non_finite_multiplier() {
+        let mut stats = mock_base_stats();
+        stats
+            .multipliers
+            .entry(Stat::Str)
+            .or_default()
+            .push(Multiplier::new(Stat::Str, f32::NAN));
+        assert_eq!(
+            CharacterStats::from_bytes(&stats.to_bytes()).err(),
+            Some(StatError::MalformedData)
+        );
+    }
+    #[test]
+    fn apply_remove_debuff() {
+        let mut stats = mock_base_stats();
+        let weakness = Debuff::new("Weakness", Stat::Str, -0.2_f32).unwrap();
+        stats.apply_debuff(&weakness);
+        assert_eq!(stats.get_stat(Stat::Str), 8);
+        stats.apply_debuff(&weakness);
+        assert_eq!(stats.get_stat(Stat::Str), 8);
+        stats.remove_debuff("Weakness");
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn positive_debuff() {
+        assert_eq!(
+            Debuff::new("Weakness", Stat::Str, 0.2_f32).err(),
+            Some(StatError::PositiveDebuff)
+        );
+    }
+    #[test]
+    fn conditional_multiplier() {
+        use std::cell::Cell;
+        let enraged = Rc::new(Cell::new(false));
+        let mut stats = mock_base_stats();
+        let flag = enraged.clone();
+        stats.add_conditional(ConditionalMultiplier::new(
+            Multiplier::new(Stat::Str, 0.2_f32),
+            Box::new(move |_| flag.get()),
+        ));
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        enraged.set(true);
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+        enraged.set(false);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn conditional_multiplier_on_other_stat() {
+        let mut stats = mock_base_stats();
+        stats.add_conditional(ConditionalMultiplier::new(
+            Multiplier::new(Stat::Str, 0.2_f32),
+            Box::new(|stats| stats.get_stat(Stat::Int) > 10),
+        ));
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        stats.set_base(Stat::Int, 11_f32);
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+    }
+    #[test]
+    fn explain() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        let breakdown = stats.explain(Stat::Str);
+        assert!(breakdown.base == 10_f32);
+        assert_eq!(breakdown.multipliers.len(), 2);
+        assert_eq!(breakdown.multipliers[0].0, Some("ring".to_string()));
+        assert_eq!(breakdown.multipliers[1].0, None);
+        assert!((breakdown.pre_round - 12_f32).abs() < 1e-5);
+        assert_eq!(breakdown.final_value, 12);
+    }
+    #[test]
+    fn try_new_multiplier() {
+        assert!(Multiplier::try_new(Stat::Str, 0.1_f32).is_ok());
+        assert_eq!(
+            Multiplier::try_new(Stat::Str, f32::NAN).err(),
+            Some(StatError::NonFinite)
+        );
+        assert_eq!(
+            Multiplier::try_new(Stat::Str, f32::INFINITY).err(),
+            Some(StatError::NonFinite)
+        );
+    }
+    #[test]
+    fn non_finite_modifiers() {
+        assert_eq!(
+            Debuff::new("poison", Stat::Str, f32::NAN).err(),
+            Some(StatError::NonFinite)
+        );
+        assert_eq!(
+            Debuff::new("poison", Stat::Str, f32::NEG_INFINITY).err(),
+            Some(StatError::NonFinite)
+        );
+        let mut stats = mock_base_stats();
+        stats.add_flat(&FlatBonus::new(Stat::Str, f32::NAN));
+        stats.push_layer(StatLayer::new().multiplier(Stat::Str, f32::NAN));
+        stats.apply_effect(&Effect::new().percent(Stat::Int, f32::INFINITY));
+        stats.add_masked_multiplier(&[Stat::Swi], f32::NAN);
+        stats.add_conditional(ConditionalMultiplier::new(
+            Multiplier::new(Stat::Str, f32::NAN),
+            Box::new(|_| true),
+        ));
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Int), 10);
+        assert_eq!(stats.get_stat(Stat::Swi), 10);
+    }
+    #[test]
+    fn add_non_finite_multiplier() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, f32::NAN);
+        assert_eq!(stats.try_add_multiplier(&mult), Err(StatError::NonFinite));
+        stats.add_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert!(stats
+            .try_add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32))
+            .is_ok());
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+    }
+    #[test]
+    fn set_level() {
+        let mut stats = mock_base_stats();
+        stats.set_growth(Stat::Str, 2_f32);
+        assert_eq!(stats.get_level(), 1);
+        stats.set_level(5);
+        assert_eq!(stats.get_level(), 5);
+        assert!(stats.get_base(Stat::Str) == Some(18_f32));
+        assert!(stats.get_base(Stat::Int) == Some(10_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        assert_eq!(stats.get_stat(Stat::Str), 27);
+        stats.set_level(1);
+        assert!(stats.get_base(Stat::Str) == Some(10_f32));
+    }
+    #[test]
+    fn set_level_growth_change() {
+        let mut stats = mock_base_stats();
+        stats.set_growth(Stat::Str, 2_f32);
+        stats.set_level(5);
+        stats.set_growth(Stat::Str, 3_f32);
+        stats.set_level(6);
+        assert!(stats.get_base(Stat::Str) == Some(21_f32));
+        stats.set_base(Stat::Str, 30_f32);
+        stats.set_level(7);
+        assert!(stats.get_base(Stat::Str) == Some(33_f32));
+    }
+    #[test]
+    fn priority_buckets_compound() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32).with_priority(1));
+        assert_eq!(stats.get_stat(Stat::Str), 225);
+    }
+    #[test]
+    fn same_priority_stacks_additively() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32).with_priority(0));
+        assert_eq!(stats.get_stat(Stat::Str), 200);
+    }
+    #[test]
+    fn undo_redo_multiplier() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        assert!(stats.undo());
+        assert_eq!(stats.get_stat(Stat::Str), 100);
+        assert!(stats.redo());
+        assert_eq!(stats.get_stat(Stat::Str), 150);
+        assert!(!stats.redo());
+    }
+    #[test]
+    fn undo_set_base_and_sub_multiplier() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, 1_f32);
+        stats.add_multiplier(&mult);
+        stats.set_base(Stat::Str, 20_f32);
+        stats.sub_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 20);
+        stats.undo();
+        assert_eq!(stats.get_stat(Stat::Str), 40);
+        stats.undo();
+        assert_eq!(stats.get_stat(Stat::Str), 20);
+        stats.set_base(Stat::Int, 5_f32);
+        assert!(!stats.redo());
     }
-    /// Adds a multiplier for a specific stat.
-    pub fn add_multiplier(&mut self, stat: &Multiplier) {
-        *self.multipliers.entry(stat.stat.clone()).or_insert(0f32) += stat.value;
+    #[test]
+    fn redo_respects_limits() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        let mult = Multiplier::new(Stat::Str, 0.2_f32).with_max_stacks(1);
+        stats.add_multiplier(&mult);
+        assert!(stats.undo());
+        stats.add_multipliers(&[mult]);
+        assert!(!stats.redo());
+        assert_eq!(stats.get_stat(Stat::Str), 120);
+        assert!(stats.undo());
+        assert_eq!(stats.get_stat(Stat::Str), 100);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, -0.5_f32));
+        assert!(stats.undo());
+        stats.set_immunity(Stat::Str, false);
+        assert!(!stats.redo());
+        assert_eq!(stats.get_stat(Stat::Str), 100);
     }
-    /// Removes a multiplier for a specific stat.
-    pub fn sub_multiplier(&mut self, stat: &Multiplier) {
-        *self.multipliers.entry(stat.stat.clone()).or_insert(0f32) -= stat.value;
+    #[test]
+    fn history_depth() {
+        let mut stats = mock_base_stats();
+        stats.set_history_depth(1);
+        stats.set_base(Stat::Str, 20_f32);
+        stats.set_base(Stat::Str, 30_f32);
+        assert!(stats.undo());
+        assert!(!stats.undo());
+        assert_eq!(stats.get_stat(Stat::Str), 20);
     }
-}
-
-impl Multiplier {
-    /// Constructs a new `Multiplier`.
-    ///
-    /// # Internals
-    /// Values are percentages, so a value of 0.1_f32 increases the specified stat by 10%.
-    pub fn new(stat: Stat, value: f32) -> Self {
-        Multiplier {
-            stat: stat,
-            value: value,
+    #[test]
+    fn stat_error_display() {
+        let stats = CharacterStats::new(hashmap![Stat::Str => 10_f32]);
+        match stats.try_get_stat(Stat::Int) {
+            Err(err @ StatError::MissingBase(_)) => {
+                assert!(err.to_string().contains("Intelligence"));
+            }
+            other => panic!("unexpected result: {:?}", other),
         }
     }
-}
-#[cfg(test)]
-mod tests {
-    use super::*;
-    fn mock_base_stats() -> CharacterStats {
-        macro_rules! hashmap {
-            ($($key:expr => $val:expr),*) => {{
-                let mut map = ::std::collections::HashMap::new();
-                $( map.insert($key, $val); )*
-                map
-            }};
-        }
-        CharacterStats::new(hashmap![
-            Stat::Str => 10_f32,
-            Stat::Int => 10_f32,
-            Stat::Swi => 10_f32
-        ])
+    #[test]
+    fn try_remove_multiplier_by_source() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32).with_source("ring"));
+        assert_eq!(
+            stats.try_remove_multiplier_by_source(Stat::Str, "amulet"),
+            Err(StatError::UnknownSource("amulet".to_string()))
+        );
+        assert_eq!(
+            stats.try_remove_multiplier_by_source(Stat::Str, "ring"),
+            Ok(())
+        );
+        assert_eq!(stats.get_stat(Stat::Str), 10);
     }
     #[test]
-    fn add_multiplier() {
+    fn threshold_crossed() {
+        let crossings = Rc::new(RefCell::new(Vec::new()));
+        let log = crossings.clone();
         let mut stats = mock_base_stats();
-        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        stats.add_threshold(Stat::Str, 15, Box::new(move |up| log.borrow_mut().push(up)));
+        let mult = Multiplier::new(Stat::Str, 0.5_f32);
         stats.add_multiplier(&mult);
-        assert_eq!(stats.get_stat(Stat::Str), 11);
+        assert_eq!(*crossings.borrow(), vec![true]);
+        stats.add_flat(&FlatBonus::new(Stat::Str, 1_f32));
+        assert_eq!(*crossings.borrow(), vec![true]);
+        stats.sub_multiplier(&mult);
+        assert_eq!(*crossings.borrow(), vec![true, false]);
     }
     #[test]
-    fn add_multipliers() {
+    fn add_operator() {
+        let a = CharacterStats::new(hashmap![Stat::Str => 10_f32]);
+        let mut b = CharacterStats::new(hashmap![Stat::Str => 5_f32, Stat::Int => 3_f32]);
+        b.add_multiplier(&Multiplier::new(Stat::Str, 1_f32));
+        let sum = a.clone() + b.clone();
+        assert!(sum.get_base(Stat::Str) == Some(15_f32));
+        assert_eq!(sum.get_stat(Stat::Str), 30);
+        assert!(sum - b == a + CharacterStats::new(hashmap![Stat::Int => 0_f32]));
+    }
+    #[test]
+    fn cached_reads() {
         let mut stats = mock_base_stats();
-        let mult = Multiplier::new(Stat::Str, 0.1_f32);
-        for _ in 0..7 {
-            stats.add_multiplier(&mult);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        let uncached = stats.clone();
+        stats.enable_cache();
+        for stat in Stat::all().iter().take(3) {
+            assert_eq!(
+                stats.get_stat(stat.clone()),
+                uncached.get_stat(stat.clone())
+            );
+            assert_eq!(
+                stats.get_stat(stat.clone()),
+                uncached.get_stat(stat.clone())
+            );
         }
-        assert_eq!(stats.get_stat(Stat::Str), 17);
+        stats.set_base(Stat::Str, 20_f32);
+        assert_eq!(stats.get_stat(Stat::Str), 30);
     }
     #[test]
-    fn add_negative_multiplier() {
+    fn get_stat_scaled() {
         let mut stats = mock_base_stats();
-        let mult = Multiplier::new(Stat::Str, -0.1_f32);
-        stats.add_multiplier(&mult);
-        assert_eq!(stats.get_stat(Stat::Str), 9);
+        stats.add_multiplier(&Multiplier::new(Stat::Swi, 0.04_f32));
+        assert_eq!(stats.get_stat_scaled(Stat::Swi, 100), Some(1040));
+        assert_eq!(stats.get_stat_scaled(Stat::Swi, 1), Some(10));
+        assert_eq!(stats.get_stat_scaled(Stat::Luck, 100), None);
     }
     #[test]
-    fn add_negative_multipliers() {
+    fn reset_stat() {
         let mut stats = mock_base_stats();
-        let mult = Multiplier::new(Stat::Str, -0.1_f32);
-        for _ in 0..7 {
-            stats.add_multiplier(&mult);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 0.5_f32));
+        stats.add_flat(&FlatBonus::new(Stat::Str, 5_f32));
+        stats.reset_stat(Stat::Str);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Int), 15);
+    }
+    #[test]
+    fn reset_stat_scaling() {
+        let mut stats = mock_base_stats();
+        stats
+            .add_scaling_multiplier(Stat::Str, 0.01_f32, Stat::Swi)
+            .unwrap();
+        stats.add_conversion(Stat::Int, Stat::Str, 0.5_f32).unwrap();
+        stats.reset_stat(Stat::Str);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert!(stats.add_conversion(Stat::Str, Stat::Swi, 0.5_f32).is_ok());
+    }
+    #[test]
+    fn preview_change() {
+        let stats = mock_base_stats();
+        let change = stats
+            .preview_change(&Multiplier::new(Stat::Str, 0.1_f32))
+            .unwrap();
+        assert!((change - 10_f32).abs() < 1e-4);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(
+            stats.preview_change(&Multiplier::new(Stat::Luck, 0.1_f32)),
+            Err(StatError::MissingBase(Stat::Luck))
+        );
+        assert!(CharacterStats::percent_change(20, 10) == -50_f32);
+    }
+    #[test]
+    #[cfg(feature = "rand")]
+    fn roll_stats() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let stats = [Stat::Str, Stat::Int, Stat::Swi];
+        let a = CharacterStats::roll_stats(30, &stats, &mut StdRng::seed_from_u64(7));
+        let b = CharacterStats::roll_stats(30, &stats, &mut StdRng::seed_from_u64(7));
+        assert!(a == b);
+        assert_eq!(a.total(), 30);
+    }
+    #[test]
+    #[cfg(feature = "rand")]
+    fn roll_from_notation() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let stats = CharacterStats::roll_from_notation("3d6", Stat::all(), &mut rng).unwrap();
+            assert!(Stat::all()
+                .iter()
+                .all(|stat| (3..=18).contains(&stats.get_stat(stat.clone()))));
         }
-        assert_eq!(stats.get_stat(Stat::Str), 3);
+        for notation in &[
+            "",
+            "3",
+            "d6",
+            "3d",
+            "0d6",
+            "3d0",
+            "3x6",
+            "-1d6",
+            "2d18446744073709551615",
+            "4000000000d6",
+        ] {
+            assert_eq!(
+                CharacterStats::roll_from_notation(notation, Stat::all(), &mut rng),
+                Err(StatError::InvalidNotation(notation.to_string()))
+            );
+        }
+        assert_eq!(
+            StatError::InvalidNotation("3x6".to_string()).to_string(),
+            "invalid dice notation \"3x6\""
+        );
     }
     #[test]
-    fn add_remove_multiplier() {
+    fn transition() {
         let mut stats = mock_base_stats();
-        let mult = Multiplier::new(Stat::Str, 0.1_f32);
-        stats.add_multiplier(&mult);
-        stats.sub_multiplier(&mult);
+        stats.set_transition(Stat::Str, 4);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 1_f32));
         assert_eq!(stats.get_stat(Stat::Str), 10);
+        stats.tick();
+        stats.tick();
+        assert_eq!(stats.get_stat(Stat::Str), 15);
+        stats.tick();
+        stats.tick();
+        assert_eq!(stats.get_stat(Stat::Str), 20);
+        stats.tick();
+        assert_eq!(stats.get_stat(Stat::Str), 20);
     }
     #[test]
-    fn add_remove_multipliers() {
+    fn threshold_during_transition() {
+        let crossings = Rc::new(RefCell::new(Vec::new()));
+        let log = crossings.clone();
         let mut stats = mock_base_stats();
-        let mult = Multiplier::new(Stat::Str, 0.1_f32);
-        for _ in 0..7 {
-            stats.add_multiplier(&mult);
+        stats.set_transition(Stat::Str, 4);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 1_f32));
+        stats.add_threshold(
+            Stat::Str,
+            15,
+            Box::new(move |reached| log.borrow_mut().push(reached)),
+        );
+        stats.add_flat(&FlatBonus::new(Stat::Int, 1_f32));
+        assert!(crossings.borrow().is_empty());
+        stats.clear_multipliers();
+        assert_eq!(*crossings.borrow(), vec![false]);
+    }
+    #[test]
+    fn multipliers_for() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 0.1_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.2_f32).with_source("amulet"));
+        let sources: Vec<Option<&str>> = stats
+            .multipliers_for(Stat::Str)
+            .iter()
+            .map(|mult| mult.source.as_deref())
+            .collect();
+        assert_eq!(sources, vec![Some("ring"), Some("amulet")]);
+    }
+    #[test]
+    fn apply_and_remove_effect() {
+        let mut stats = mock_base_stats();
+        let effect = Effect::new()
+            .flat(Stat::Str, 5_f32)
+            .percent(Stat::Str, 0.2_f32);
+        let handle = stats.apply_effect(&effect);
+        assert_eq!(stats.get_stat(Stat::Str), 18);
+        stats.remove_effect(handle);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        stats.remove_effect(handle);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn conversion() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 10_f32, Stat::Int => 20_f32]);
+        assert_eq!(stats.add_conversion(Stat::Int, Stat::Str, 0.1_f32), Ok(()));
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+        assert_eq!(stats.get_stat(Stat::Int), 20);
+    }
+    #[test]
+    fn cyclic_conversion() {
+        let mut stats = mock_base_stats();
+        assert_eq!(
+            stats.add_conversion(Stat::Str, Stat::Str, 0.1_f32),
+            Err(StatError::CyclicDependency)
+        );
+        stats.add_conversion(Stat::Str, Stat::Int, 0.1_f32).unwrap();
+        stats.add_conversion(Stat::Int, Stat::Swi, 0.1_f32).unwrap();
+        assert_eq!(
+            stats.add_conversion(Stat::Swi, Stat::Str, 0.1_f32),
+            Err(StatError::CyclicDependency)
+        );
+    }
+    #[test]
+    fn kv_round_trip() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        let pairs = stats.to_kv();
+        assert_eq!(pairs[0], ("Strength".to_string(), 15_f32));
+        let imported = CharacterStats::from_kv(&pairs).unwrap();
+        assert_eq!(imported.effective_stats(), stats.effective_stats());
+        assert_eq!(
+            CharacterStats::from_kv(&[("Wisdom".to_string(), 1_f32)]).map(|_| ()),
+            Err(StatError::UnknownStat("Wisdom".to_string()))
+        );
+    }
+    #[test]
+    fn decay_out_of_range() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32).with_decay(3_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 0.5_f32).with_decay(f32::NAN));
+        stats.tick();
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Int), 15);
+        let mut bad = mock_base_stats();
+        bad.multipliers
+            .entry(Stat::Str)
+            .or_default()
+            .push(Multiplier {
+                decay: Some(f32::NAN),
+                ..Multiplier::new(Stat::Str, 0.5_f32)
+            });
+        assert_eq!(
+            CharacterStats::from_bytes(&bad.to_bytes()).err(),
+            Some(StatError::MalformedData)
+        );
+    }
+    #[test]
+    fn decaying_multiplier() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 100_f32]);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, -0.3_f32).with_decay(0.5_f32));
+        assert!((stats.get_multiplier(Stat::Str) + 0.3_f32).abs() < 1e-6);
+        stats.tick();
+        assert!((stats.get_multiplier(Stat::Str) + 0.15_f32).abs() < 1e-6);
+        stats.tick();
+        assert!((stats.get_multiplier(Stat::Str) + 0.075_f32).abs() < 1e-6);
+        for _ in 0..20 {
+            stats.tick();
         }
-        for _ in 0..7 {
-            stats.sub_multiplier(&mult);
+        assert!(stats.multipliers_for(Stat::Str).is_empty());
+        assert_eq!(stats.get_stat(Stat::Str), 100);
+    }
+    #[test]
+    fn freeze() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        let frozen = stats.freeze();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        assert_eq!(frozen.get_stat(Stat::Str), 15);
+        assert!(frozen.get_base(Stat::Str) == Some(10_f32));
+        assert_eq!(frozen.effective_stats().len(), 3);
+        assert_eq!(stats.get_stat(Stat::Str), 20);
+    }
+    #[test]
+    // The cache behind the `RefCell` is not part of the hash.
+    #[allow(clippy::mutable_key_type)]
+    fn stats_as_map_key() {
+        let mut a = mock_base_stats();
+        a.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        let mut b = mock_base_stats();
+        b.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        let mut results = HashMap::new();
+        results.insert(a, 1);
+        results.insert(b, 2);
+        results.insert(mock_base_stats(), 3);
+        assert_eq!(results.len(), 2);
+    }
+    #[test]
+    fn immunity_conditional_and_merge() {
+        let mut stats = mock_base_stats();
+        stats.set_immunity(Stat::Swi, true);
+        stats.add_conditional(ConditionalMultiplier::new(
+            Multiplier::new(Stat::Swi, -0.5_f32),
+            Box::new(|_| true),
+        ));
+        assert_eq!(stats.get_stat(Stat::Swi), 10);
+        let mut other = mock_base_stats();
+        other.add_multiplier(&Multiplier::new(Stat::Swi, -0.5_f32));
+        other.add_multiplier(&Multiplier::new(Stat::Swi, 0.5_f32));
+        stats.merge(&other);
+        assert_eq!(stats.get_stat(Stat::Swi), 30);
+    }
+    #[test]
+    fn negative_immunity() {
+        let mut stats = mock_base_stats();
+        stats.set_immunity(Stat::Swi, true);
+        assert!(stats.is_immune(Stat::Swi));
+        assert!(!stats.is_immune(Stat::Str));
+        stats.add_multiplier(&Multiplier::new(Stat::Swi, -0.2_f32));
+        stats.apply_debuff(&Debuff::new("slow", Stat::Swi, -0.2_f32).unwrap());
+        assert_eq!(stats.get_stat(Stat::Swi), 10);
+        stats.add_multiplier(&Multiplier::new(Stat::Swi, 0.2_f32));
+        assert_eq!(stats.get_stat(Stat::Swi), 12);
+    }
+    #[test]
+    fn with_multiplier() {
+        let stats = mock_base_stats();
+        let boosted = stats.with_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        assert_eq!(boosted.get_stat(Stat::Str), 15);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn defined_stats() {
+        let stats = CharacterStats::new(hashmap![Stat::Int => 1_f32, Stat::Str => 1_f32]);
+        assert_eq!(stats.defined_stats(), vec![Stat::Str, Stat::Int]);
+        assert!(!stats.defined_stats().contains(&Stat::Swi));
+    }
+    #[test]
+    fn global_multiplier() {
+        let mut stats = mock_base_stats();
+        stats.add_global_multiplier(0.25_f32);
+        for stat in &[Stat::Str, Stat::Int, Stat::Swi] {
+            assert_eq!(stats.get_stat(stat.clone()), 13);
         }
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.25_f32));
+        assert_eq!(stats.get_stat(Stat::Str), 15);
+        stats.remove_global_multiplier(0.25_f32);
+        assert_eq!(stats.get_stat(Stat::Int), 10);
+    }
+    #[test]
+    fn scale_bases() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        stats.scale_bases(1.5_f32);
+        assert!(stats.get_base(Stat::Str) == Some(15_f32));
+        assert_eq!(stats.get_stat(Stat::Str), 17);
+        assert_eq!(stats.get_stat(Stat::Int), 15);
+    }
+    #[test]
+    fn preview_at_level() {
+        let mut stats = mock_base_stats();
+        stats.set_growth(Stat::Str, 2_f32);
+        let preview = stats.preview_at_level(10);
+        assert_eq!(preview[&Stat::Str], 28);
+        assert_eq!(preview[&Stat::Int], 10);
+        assert_eq!(stats.get_level(), 1);
         assert_eq!(stats.get_stat(Stat::Str), 10);
     }
     #[test]
-    fn add_remove_negative_multiplier() {
+    fn percent_in_range() {
+        let mut stats = mock_base_stats();
+        let percent = Percent::new(0.5_f32).unwrap();
+        stats.add_multiplier(&Multiplier::from_percent(Stat::Str, percent));
+        assert_eq!(stats.get_stat(Stat::Str), 15);
+        assert!(Percent::new(-1_f32).is_ok());
+    }
+    #[test]
+    fn percent_out_of_range() {
+        assert_eq!(Percent::new(-2_f32), Err(StatError::PercentOutOfRange));
+        assert_eq!(Percent::new(f32::NAN), Err(StatError::NonFinite));
+    }
+    #[test]
+    fn delta() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        stats.set_base(Stat::Luck, 3_f32);
+        let delta = stats.delta(&mock_base_stats());
+        assert_eq!(delta[&Stat::Str], 5);
+        assert_eq!(delta[&Stat::Int], 0);
+        assert!(!delta.contains_key(&Stat::Luck));
+    }
+    #[test]
+    fn quadratic_cost_curve() {
+        let mut stats = mock_base_stats();
+        stats.set_cost_curve(CostCurve::Quadratic);
+        stats.add_points(20);
+        stats.allocate(Stat::Str, 1).unwrap();
+        assert_eq!(stats.available_points(), 19);
+        stats.allocate(Stat::Str, 2).unwrap();
+        assert_eq!(stats.available_points(), 6);
+        assert_eq!(stats.get_stat(Stat::Str), 13);
+        assert_eq!(stats.allocate(Stat::Str, 1), Err(StatError::OutOfPoints));
+        stats.reset_points();
+        assert_eq!(stats.available_points(), 20);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+    }
+    #[test]
+    fn tick_returns_expired() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(
+            &Multiplier::new(Stat::Str, 0.1_f32)
+                .with_source("haste")
+                .with_duration(1),
+        );
+        stats.add_multiplier(
+            &Multiplier::new(Stat::Str, 0.2_f32)
+                .with_source("might")
+                .with_duration(3),
+        );
+        let expired = stats.tick();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].source.as_deref(), Some("haste"));
+        assert!(stats.tick().is_empty());
+    }
+    #[test]
+    fn scale_multiplier() {
+        let mult = Multiplier::new(Stat::Str, 0.1_f32) * 2_f32;
+        assert!((mult.percentage() - 20_f32).abs() < 1e-4);
+    }
+    #[test]
+    fn sum_multipliers() {
+        let mult = Multiplier::new(Stat::Str, 0.1_f32) + Multiplier::new(Stat::Str, 0.2_f32);
         let mut stats = mock_base_stats();
-        let mult = Multiplier::new(Stat::Str, -0.1_f32);
         stats.add_multiplier(&mult);
-        stats.sub_multiplier(&mult);
+        assert_eq!(stats.get_stat(Stat::Str), 13);
+    }
+    #[test]
+    #[should_panic]
+    fn sum_multipliers_of_different_stats() {
+        let _ = Multiplier::new(Stat::Str, 0.1_f32) + Multiplier::new(Stat::Int, 0.1_f32);
+    }
+    #[test]
+    fn stat_override() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Swi, 0.5_f32));
+        stats.set_override(Stat::Swi, 1);
+        assert_eq!(stats.get_stat(Stat::Swi), 1);
+        stats.clear_override(Stat::Swi);
+        assert_eq!(stats.get_stat(Stat::Swi), 15);
+    }
+    #[test]
+    fn stat_override_feeds_dependents() {
+        let mut stats = mock_base_stats();
+        stats.add_conversion(Stat::Swi, Stat::Int, 0.5_f32).unwrap();
+        stats
+            .add_scaling_multiplier(Stat::Str, 0.01_f32, Stat::Swi)
+            .unwrap();
+        stats.set_override(Stat::Swi, 1);
+        assert!(stats.get_stat_f32(Stat::Swi) == Some(1_f32));
+        assert_eq!(stats.get_stat(Stat::Int), 11);
+        assert!((stats.get_stat_f32(Stat::Str).unwrap() - 10.1_f32).abs() < 1e-4);
+        stats.reset_stat(Stat::Swi);
+        assert!(stats.get_stat(Stat::Swi) as f32 == stats.get_base(Stat::Swi).unwrap().round());
+    }
+    #[test]
+    fn stat_override_scaled_and_explained() {
+        let mut stats = mock_base_stats();
+        stats.set_override(Stat::Str, 1);
+        assert_eq!(stats.get_stat_scaled(Stat::Str, 100), Some(100));
+        let breakdown = stats.explain(Stat::Str);
+        assert!(breakdown.pre_round == 1_f32);
+        assert_eq!(breakdown.final_value, 1);
+    }
+    #[test]
+    fn deterministic_bytes() {
+        let build = || {
+            let mut stats =
+                CharacterStats::from_iter(Stat::all().iter().map(|stat| (stat.clone(), 10_f32)));
+            for stat in Stat::all() {
+                stats.add_multiplier(&Multiplier::new(stat.clone(), 0.1_f32));
+            }
+            stats
+        };
+        assert_eq!(build().to_bytes(), build().to_bytes());
+        assert_eq!(build().to_kv(), build().to_kv());
+    }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deterministic_serde() {
+        let build = || {
+            let mut stats =
+                CharacterStats::from_iter(Stat::all().iter().map(|stat| (stat.clone(), 10_f32)));
+            stats.set_floor(Stat::Luck, 1);
+            stats
+        };
+        let a = ::serde_json::to_string(&build()).unwrap();
+        let b = ::serde_json::to_string(&build()).unwrap();
+        assert_eq!(a, b);
+    }
+    #[test]
+    fn sort_stats() {
+        let mut stats = vec![
+            Stat::Cha,
+            Stat::Int,
+            Stat::Luck,
+            Stat::Str,
+            Stat::Vit,
+            Stat::Dex,
+            Stat::Swi,
+        ];
+        stats.sort();
+        assert_eq!(stats, Stat::all().to_vec());
+    }
+    #[test]
+    fn on_expire() {
+        let expired = Rc::new(RefCell::new(Vec::new()));
+        let log = expired.clone();
+        let mut stats = mock_base_stats();
+        stats.on_expire(Box::new(move |mult| {
+            log.borrow_mut().push(mult.stat.clone())
+        }));
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 0.1_f32).with_duration(1));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_duration(2));
+        stats.tick();
+        assert_eq!(*expired.borrow(), vec![Stat::Int]);
+    }
+    #[test]
+    fn from_base_slice() {
+        let stats = CharacterStats::from_base_slice(&[(Stat::Str, 10.0), (Stat::Int, 5.0)]);
         assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Int), 5);
+        assert!(stats.get_base(Stat::Swi).is_none());
     }
     #[test]
-    fn add_remove_negative_multipliers() {
+    fn average_and_median() {
         let mut stats = mock_base_stats();
-        let mult = Multiplier::new(Stat::Str, -0.1_f32);
-        for _ in 0..7 {
-            stats.add_multiplier(&mult);
+        assert!(stats.average() == 10_f32);
+        assert!(stats.median() == 10_f32);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 2_f32));
+        assert!((stats.average() - 50_f32 / 3_f32).abs() < 1e-4);
+        assert!(stats.median() == 10_f32);
+        stats.set_base(Stat::Luck, 0_f32);
+        assert!(stats.median() == 10_f32);
+        assert!(CharacterStats::new(HashMap::new()).average() == 0_f32);
+    }
+    #[test]
+    fn copy_multipliers_from() {
+        let mut leader = mock_base_stats();
+        leader.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        let mut follower = CharacterStats::new(hashmap![Stat::Str => 20_f32]);
+        follower.add_multiplier(&Multiplier::new(Stat::Str, 1_f32));
+        follower.copy_multipliers_from(&leader);
+        assert_eq!(follower.get_stat(Stat::Str), 30);
+        assert_eq!(leader.get_stat(Stat::Str), 15);
+    }
+    #[test]
+    fn copy_multipliers_from_immunity() {
+        let mut leader = mock_base_stats();
+        leader.add_multiplier(&Multiplier::new(Stat::Swi, -0.5_f32));
+        leader.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32));
+        let mut follower = mock_base_stats();
+        follower.set_immunity(Stat::Swi, true);
+        follower.copy_multipliers_from(&leader);
+        assert_eq!(follower.get_stat(Stat::Swi), 10);
+        assert_eq!(follower.get_stat(Stat::Str), 15);
+    }
+    #[test]
+    fn array_backend_matches_map_backend() {
+        let bases = [10_f32, 8_f32, 6_f32, 4_f32, 3_f32, 2_f32, 1_f32];
+        let mut array = CharacterStats::new_array(bases);
+        let mut map = CharacterStats::from_iter(
+            Stat::all()
+                .iter()
+                .map(|stat| (stat.clone(), bases[stat.index()])),
+        );
+        for stats in [&mut array, &mut map].iter_mut() {
+            stats.add_multiplier(&Multiplier::new(Stat::Str, 0.25_f32));
+            stats.add_flat(&FlatBonus::new(Stat::Int, 2_f32));
+            stats.add_base(Stat::Luck, 1.5_f32);
+            stats.scale_bases(1.5_f32);
         }
-        for _ in 0..7 {
-            stats.sub_multiplier(&mult);
+        for stat in Stat::all() {
+            assert_eq!(array.get_stat(stat.clone()), map.get_stat(stat.clone()));
         }
-        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert!(array == map);
+        assert_eq!(array.to_bytes(), map.to_bytes());
     }
     #[test]
-    fn get_without_multipliers() {
+    fn active_effects() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 0.1_f32).with_source("ring"));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.2_f32).with_duration(3));
+        let effects = stats.active_effects();
+        assert_eq!(effects.len(), 2);
+        assert_eq!(effects[0].stat, Stat::Str);
+        assert_eq!(effects[0].remaining, Some(3));
+        assert_eq!(effects[1].remaining, None);
+        assert_eq!(effects[1].source, Some("ring".to_string()));
+    }
+    #[test]
+    fn soft_cap() {
+        let mut stats = mock_base_stats();
+        stats.set_soft_cap(Stat::Str, 20_f32, 0.5_f32);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 2_f32));
+        assert_eq!(stats.get_stat(Stat::Str), 25);
+        stats.set_soft_cap(Stat::Int, 20_f32, 0.5_f32);
+        assert_eq!(stats.get_stat(Stat::Int), 10);
+    }
+    #[test]
+    fn validate() {
         let stats = mock_base_stats();
+        assert_eq!(stats.validate(&[Stat::Str, Stat::Int, Stat::Swi]), Ok(()));
+        let err = stats
+            .validate(&[Stat::Str, Stat::Luck, Stat::Cha])
+            .unwrap_err();
+        assert_eq!(err, StatError::MissingBase(Stat::Luck));
+        assert!(err.to_string().contains("Luck"));
+    }
+    #[test]
+    fn masked_multiplier() {
+        let mut stats = mock_base_stats();
+        let handle = stats.add_masked_multiplier(&[Stat::Str, Stat::Swi], 0.1_f32);
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+        assert_eq!(stats.get_stat(Stat::Swi), 11);
+        assert_eq!(stats.get_stat(Stat::Int), 10);
+        stats.remove_masked(handle);
+        assert_eq!(stats.get_stat(Stat::Str), 10);
+        assert_eq!(stats.get_stat(Stat::Swi), 10);
+    }
+    #[test]
+    fn masked_multiplier_immunity() {
+        let mut stats = mock_base_stats();
+        stats.set_immunity(Stat::Swi, false);
+        stats.add_masked_multiplier(&[Stat::Str, Stat::Swi], -0.5_f32);
+        assert_eq!(stats.get_stat(Stat::Str), 5);
+        assert_eq!(stats.get_stat(Stat::Swi), 10);
+    }
+    #[test]
+    fn summary() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        let summary = stats.summary();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "Strength: 11 (base 10, +10%)");
+        assert_eq!(lines[1], "Intelligence: 10 (base 10, +0%)");
+    }
+    #[test]
+    fn regen() {
+        let mut stats = mock_base_stats();
+        stats.set_regen(Stat::Swi, 2_f32, 15_f32);
+        stats.tick();
+        assert!(stats.get_base(Stat::Swi) == Some(12_f32));
+        stats.tick();
+        stats.tick();
+        assert!(stats.get_base(Stat::Swi) == Some(15_f32));
+        assert!(stats.get_base(Stat::Str) == Some(10_f32));
+    }
+    #[test]
+    fn prune() {
+        let mut stats = mock_base_stats();
+        let mult = Multiplier::new(Stat::Str, 0.1_f32);
+        stats.add_multiplier(&mult);
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 0.1_f32));
+        stats.sub_multiplier(&mult);
+        stats.prune();
+        assert!(!stats.has_multiplier(Stat::Str));
+        assert!(!stats.multipliers.contains_key(&Stat::Str));
+        assert!(stats.multipliers.contains_key(&Stat::Int));
+    }
+    #[test]
+    fn prune_keeps_timed_multipliers() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.5_f32).with_duration(1));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, -0.5_f32));
+        stats.prune();
+        stats.tick();
+        assert_eq!(stats.get_stat(Stat::Str), 5);
+    }
+    #[test]
+    fn prune_keeps_buckets_and_curve() {
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 1000_f32]);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, -0.1_f32).with_priority(1));
+        let before = stats.get_stat(Stat::Str);
+        stats.prune();
+        assert_eq!(stats.get_stat(Stat::Str), before);
+        let mut stats = CharacterStats::new(hashmap![Stat::Str => 1000_f32]);
+        stats.set_curve(MultiplierCurve::DiminishingHarmonic);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, -0.2_f32));
+        let before = stats.get_stat(Stat::Str);
+        stats.prune();
+        assert_eq!(stats.get_stat(Stat::Str), before);
+    }
+    #[test]
+    fn meets_requirements() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        assert!(!stats.meets_requirements(&[(Stat::Str, 15)]));
+        assert!(stats.meets_requirements(&[(Stat::Str, 10), (Stat::Int, 10)]));
+        assert!(!stats.meets_requirements(&[(Stat::Luck, 0)]));
+    }
+    #[test]
+    fn lerp() {
+        let a = mock_base_stats();
+        let mut b = CharacterStats::new(hashmap![Stat::Str => 20_f32, Stat::Luck => 4_f32]);
+        b.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        let half = CharacterStats::lerp(&a, &b, 0.5_f32);
+        assert!(half.get_base(Stat::Str) == Some(15_f32));
+        assert!(half.get_base(Stat::Int) == Some(10_f32));
+        assert!(half.get_base(Stat::Luck) == Some(4_f32));
+        assert_eq!(half.get_stat(Stat::Str), 17);
+        let past = CharacterStats::lerp(&a, &b, 2_f32);
+        assert!(past.get_base(Stat::Str) == Some(20_f32));
+    }
+    #[test]
+    fn lerp_cached() {
+        let a = mock_base_stats();
+        let mut b = CharacterStats::new(hashmap![Stat::Str => 20_f32]);
+        b.enable_cache();
+        assert_eq!(b.get_stat(Stat::Str), 20);
+        let half = CharacterStats::lerp(&a, &b, 0.5_f32);
+        assert_eq!(half.get_stat(Stat::Str), 15);
+    }
+    #[test]
+    fn highest_and_lowest() {
+        let mut stats = mock_base_stats();
+        assert_eq!(stats.lowest(), Some((Stat::Str, 10)));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32));
+        assert_eq!(stats.highest(), Some((Stat::Str, 11)));
+        assert_eq!(stats.lowest(), Some((Stat::Int, 10)));
+        assert_eq!(CharacterStats::new(HashMap::new()).highest(), None);
+    }
+    #[test]
+    fn scaling_multiplier() {
+        let mut stats = mock_base_stats();
+        assert!(stats
+            .add_scaling_multiplier(Stat::Str, 0.01_f32, Stat::Swi)
+            .is_ok());
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+        stats.set_base(Stat::Swi, 20_f32);
+        assert_eq!(stats.get_stat(Stat::Str), 12);
+        assert_eq!(
+            stats.add_scaling_multiplier(Stat::Swi, 0.01_f32, Stat::Str),
+            Err(StatError::CyclicDependency)
+        );
+        assert_eq!(
+            stats.add_scaling_multiplier(Stat::Int, 0.01_f32, Stat::Int),
+            Err(StatError::CyclicDependency)
+        );
+        assert_eq!(
+            stats.add_conversion(Stat::Str, Stat::Swi, 0.1_f32),
+            Err(StatError::CyclicDependency)
+        );
+    }
+    #[test]
+    fn uniform() {
+        let stats = CharacterStats::uniform(10_f32);
+        assert_eq!(stats.get_stat(Stat::Int), 10);
+        assert_eq!(stats.defined_stats(), Stat::all().to_vec());
+        assert!(Stat::all()
+            .iter()
+            .all(|stat| stats.get_base(stat.clone()) == Some(10_f32)));
+    }
+    #[test]
+    fn multiplier_from_category() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_category("gear"));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.05_f32).with_category("gear"));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_category("spell"));
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 0.2_f32).with_category("spell"));
+        assert!((stats.multiplier_from_category(Stat::Str, "gear") - 0.15_f32).abs() < 1e-6);
+        assert!((stats.multiplier_from_category(Stat::Str, "spell") - 0.1_f32).abs() < 1e-6);
+        assert!(stats.multiplier_from_category(Stat::Str, "aura") == 0_f32);
+    }
+    #[test]
+    fn checked_get_stat() {
+        let stats = CharacterStats::new(hashmap![Stat::Str => 10_f32, Stat::Int => f32::NAN]);
+        assert_eq!(stats.checked_get_stat(Stat::Str), Some(10));
+        assert_eq!(stats.checked_get_stat(Stat::Int), None);
+        assert_eq!(stats.get_stat(Stat::Int), 0);
+        assert_eq!(stats.checked_get_stat(Stat::Luck), None);
+    }
+    #[test]
+    fn export_import_multipliers() {
+        let mut stats = mock_base_stats();
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 0.1_f32).with_source("ring"));
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 0.2_f32));
+        let set = stats.export_multipliers();
+        stats.clear_multipliers();
+        stats.add_multiplier(&Multiplier::new(Stat::Swi, 0.5_f32));
         assert_eq!(stats.get_stat(Stat::Str), 10);
+        stats.import_multipliers(set);
+        assert_eq!(stats.get_stat(Stat::Str), 11);
+        assert_eq!(stats.get_stat(Stat::Int), 12);
+        assert_eq!(stats.get_stat(Stat::Swi), 10);
+        assert!(stats
+            .try_remove_multiplier_by_source(Stat::Str, "ring")
+            .is_ok());
+    }
+    #[test]
+    fn multiplier_cap() {
+        let mut stats = mock_base_stats();
+        stats.set_multiplier_cap(Stat::Str, 1_f32);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 1_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, 1_f32));
+        assert_eq!(stats.get_stat(Stat::Str), 20);
+        assert!(stats.get_multiplier(Stat::Str) == 2_f32);
+        stats.add_multiplier(&Multiplier::new(Stat::Int, 2_f32));
+        assert_eq!(stats.get_stat(Stat::Int), 30);
+    }
+    #[test]
+    fn multiplier_cap_negative() {
+        let mut stats = mock_base_stats();
+        stats.set_multiplier_cap(Stat::Str, 0.5_f32);
+        stats.add_multiplier(&Multiplier::new(Stat::Str, -0.4_f32));
+        stats.add_multiplier(&Multiplier::new(Stat::Str, -0.4_f32));
+        assert_eq!(stats.get_stat(Stat::Str), 5);
+        assert!((stats.get_multiplier(Stat::Str) + 0.8_f32).abs() < 1e-6);
     }
-}
\ No newline at end of file
+    #[test]
+    fn diff() {
+        let stats = mock_base_stats();
+        let boosted = stats.with_multiplier(&Multiplier::new(Stat::Str, 0.2_f32));
+        assert_eq!(stats.diff(&boosted), vec![(Stat::Str, 2)]);
+        assert_eq!(boosted.diff(&stats), vec![(Stat::Str, -2)]);
+        assert!(stats.diff(&stats).is_empty());
+    }
+}